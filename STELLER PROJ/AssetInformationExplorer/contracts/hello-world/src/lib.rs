@@ -1,10 +1,10 @@
 #![allow(non_snake_case)]
 #![no_std]
-use soroban_sdk::{contract, contracttype, contractimpl, log, Env, Symbol, String, Address, symbol_short};
+use soroban_sdk::{contract, contractclient, contracterror, contracttype, contractimpl, log, panic_with_error, xdr::ToXdr, Env, IntoVal, Symbol, String, Address, Bytes, BytesN, I256, Map, Val, Vec, symbol_short};
 
 // Structure to store asset information
 #[contracttype]
-#[derive(Clone)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct AssetInfo {
     pub asset_code: String,
     pub issuer: Address,
@@ -12,127 +12,5006 @@ pub struct AssetInfo {
     pub description: String,
     pub is_active: bool,
     pub registration_time: u64,
+    pub last_updated: u64,
+    pub is_verified: bool,
+    pub description_locked: bool,
+    pub metadata_uri: Option<String>,
+    pub successor: Option<String>,
+    pub issuer_verified: bool,
+    pub category: Option<Symbol>,
+    pub supply_known: bool,
+    pub decimals: u32,
+    pub reserved: bool,
+    pub max_supply: Option<i128>,
+}
+
+// Asset record as stored by the first release, before the optional fields existed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LegacyAssetInfo {
+    pub asset_code: String,
+    pub issuer: Address,
+    pub total_supply: i128,
+    pub description: String,
+    pub is_active: bool,
+    pub registration_time: u64,
+}
+
+// Supply in effect from a given timestamp onwards
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SupplyCheckpoint {
+    pub timestamp: u64,
+    pub supply: i128,
+}
+
+// Errors surfaced by the contract
+#[contracterror]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AssetNotFound = 1,
+    AssetAlreadyExists = 2,
+    InvalidSupply = 3,
+    InvalidCode = 4,
+    InvalidIssuer = 5,
+    IssuerNotAllowed = 6,
+    Paused = 7,
+    NotInitialized = 8,
+    AlreadyInitialized = 9,
+    RegistryClearing = 10,
+    DuplicateInBatch = 11,
+    DescriptionTooShort = 12,
+    DescriptionLocked = 13,
+    InvalidMetadataUri = 14,
+    InvalidTtlConfig = 15,
+    InvalidEventLevel = 16,
+    IdempotencyKeyUsed = 17,
+    NotRegistrar = 18,
+    NotReserved = 19,
+    NotIssuerOrDelegate = 20,
+    FractionalSupply = 21,
+    SupplyAboveMax = 22,
+    SupplyOverflow = 23,
+    AssetInactive = 24,
+    InvalidSuccessor = 25,
+    InvalidDecimals = 26,
+    InvalidRange = 27,
+    AssetActive = 28,
+    AssetReserved = 29,
+    HistoryTruncated = 30,
+}
+
+// Outcome of a registration attempt
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegisterResult {
+    Registered,
+    AlreadyExists,
+    InvalidCode,
+    InvalidSupply,
+    Unauthorized,
+    Paused,
+    InvalidIssuer,
+    Clearing,
+    Conflict,
+    InvalidDescription,
+    FractionalSupply,
+}
+
+// How far the contract's storage lifetime is extended on writes
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlConfig {
+    pub enabled: bool,
+    pub threshold: u32,
+    pub extend_to: u32,
+}
+
+// Registry-wide stats for one issuer
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IssuerSummary {
+    pub asset_count: u64,
+    pub active_count: u64,
+    pub aggregate_supply: i128,
+    pub reputation: i32,
+}
+
+// Price source consulted by get_asset_value; quotes the value of one whole unit
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    fn price(env: Env, asset_code: String) -> i128;
+}
+
+// One entry in the recent activity feed
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Activity {
+    pub kind: Symbol,
+    pub asset_code: String,
+    pub timestamp: u64,
+}
+
+// Settings applied together by the one-call constructor
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryConfig {
+    pub ttl: TtlConfig,
+    pub registration_mode: RegistrationMode,
+}
+
+// Who may register new assets
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RegistrationMode {
+    Open,
+    Allowlist,
 }
 
 // Mapping asset code to AssetInfo
 #[contracttype]
 pub enum AssetBook {
-    Asset(String)
+    Asset(String),
+    Index(u64),
+    History(String),
+    Delegate(String),
+    IndexOf(String),
+    Allowed(Address),
+    Reputation(Address),
+    CategoryCount(Symbol),
+    Issuer(Address),
+    Idempotency(Address, BytesN<32>),
+    Registrar(Address),
+    PriceOracle(String),
+    LiveUntil(String),
+    HistoryTruncated(String),
 }
 
 // Counter for total registered assets
 const ASSET_COUNT: Symbol = symbol_short!("A_COUNT");
 
-#[contract]
-pub struct AssetExplorerContract;
+// Counter for registered assets that are currently active
+const ACTIVE_COUNT: Symbol = symbol_short!("A_ACTIVE");
 
-#[contractimpl]
-impl AssetExplorerContract {
+// Next free index slot; slots of removed assets are never reused
+const NEXT_INDEX: Symbol = symbol_short!("A_NEXT");
+
+// Persistent entries written and not yet removed, for estimate_entry_count
+const ENTRY_COUNT: Symbol = symbol_short!("ENTRIES");
+
+// Categories holding at least one active asset
+const CATEGORIES: Symbol = symbol_short!("CATS");
+
+// Sum of total_supply across active assets, stored 256 bits wide
+const AGG_SUPPLY: Symbol = symbol_short!("AGG_SUP");
+
+// Sequence number of the last published event
+const EVENT_SEQ: Symbol = symbol_short!("EVT_SEQ");
+
+// Registry administrator, set once by the constructor or initialize
+const ADMIN: Symbol = symbol_short!("ADMIN");
+
+// Description used when an asset is registered without one
+const DEFAULT_DESC: Symbol = symbol_short!("DEF_DESC");
+
+// Name and description of the registry itself
+const REGISTRY_INFO: Symbol = symbol_short!("REG_INFO");
+
+// When set, only verified assets appear in listings
+const REQUIRE_VERIFIED: Symbol = symbol_short!("REQ_VERIF");
+
+// Set while the admin has halted state changes
+const PAUSED: Symbol = symbol_short!("PAUSED");
+
+// Whether inactive codes may be claimed by a new registration; off unless
+// the admin enables it, so a delisted record cannot be replaced by anyone else
+const CODE_REUSE: Symbol = symbol_short!("REUSE");
+
+// Supply change, in basis points of the old supply, above which an alert event is emitted
+const ALERT_BPS: Symbol = symbol_short!("ALERT_BPS");
+
+// Set while clear_registry is part-way through deleting records
+const CLEARING: Symbol = symbol_short!("CLEARING");
+
+// How much the contract publishes, one of the EVENTS_* levels; defaults to EVENTS_ALL
+const EVENT_LEVEL: Symbol = symbol_short!("EVT_LVL");
+
+// Event levels: nothing, state changes only, or everything including events from
+// reads (none of which publish yet)
+const EVENTS_NONE: u32 = 0;
+const EVENTS_STATE: u32 = 1;
+const EVENTS_ALL: u32 = 2;
+
+// Storage layout version recorded at construction
+const VERSION: Symbol = symbol_short!("VERSION");
+
+// Storage layout version written by this build of the contract
+const SCHEMA_VERSION: u32 = 1;
+
+// Most recent activity entries, oldest first
+const ACTIVITY: Symbol = symbol_short!("ACTIVITY");
+
+// Number of activity entries kept before the oldest is dropped
+const MAX_ACTIVITY: u32 = 32;
+
+// Number of supply checkpoints kept per asset before the oldest is dropped
+const MAX_HISTORY: u32 = 20;
+
+// Minimum description length in bytes; 0 or unset disables the check
+const MIN_DESC_LEN: Symbol = symbol_short!("MIN_DESC");
+
+// Current TtlConfig, extending to 5000 ledgers by default
+const TTL_CONFIG: Symbol = symbol_short!("TTL_CFG");
+
+// Current RegistrationMode, open to every issuer by default
+const REG_MODE: Symbol = symbol_short!("REG_MODE");
+
+// Longest asset code accepted, matching Stellar's alphanum12 assets
+const MAX_CODE_LEN: u32 = 12;
+
+// When set, supplies must be whole multiples of 10^decimals
+const WHOLE_UNITS: Symbol = symbol_short!("WHOLE");
+
+// Decimals assumed for new assets, matching Stellar's 7-digit amounts
+const DEFAULT_DECIMALS: u32 = 7;
+
+// Most decimals an asset may declare
+const MAX_DECIMALS: u32 = 18;
+
+// Longest metadata URI accepted
+const MAX_URI_LEN: u32 = 256;
+
+// Bounds for the admin-maintained issuer reputation score
+const MIN_REPUTATION: i32 = -1000;
+const MAX_REPUTATION: i32 = 1000;
+
+// Index slots sampled by get_random_asset before giving up
+const MAX_RANDOM_ATTEMPTS: u32 = 8;
+
+// Upper bound on the number of records returned by one listing call
+const MAX_PAGE_SIZE: u32 = 50;
+
+// Upper bound on the number of index slots one scanning call reads; scans stop
+// there and return what they found so far along with the slot to resume from
+const MAX_SCAN: u64 = 100;
+
+// Ledgers a used idempotency key is remembered for (about a day), long enough to
+// cover client retries without keeping every key forever
+const IDEMPOTENCY_TTL: u32 = 17_280;
+
+// Read the stored record for an asset code, if any, extending its entries' lifetime.
+// Records older versions kept in instance storage are moved over on first read.
+fn read_asset(env: &Env, asset_code: &String) -> Option<AssetInfo> {
+    let key = AssetBook::Asset(asset_code.clone());
+    if let Some(asset) = env.storage().persistent().get(&key) {
+        bump_asset_ttl(env, &asset);
+        return Some(asset);
+    }
+    
+    migrate_legacy_asset(env, asset_code)
+}
+
+// Store an asset's record under its code, extending its entries' lifetime
+fn write_asset(env: &Env, asset: &AssetInfo) {
+    set_entry(env, &AssetBook::Asset(asset.asset_code.clone()), asset);
+    keep_asset_alive(env, asset);
+}
+
+// Move a first-release record left in instance storage to persistent storage,
+// filling in defaults for the fields added since. The first release counted the
+// record in ASSET_COUNT but kept nothing else, so it is indexed, listed under its
+// issuer and tracked as active here.
+fn migrate_legacy_asset(env: &Env, asset_code: &String) -> Option<AssetInfo> {
+    let key = AssetBook::Asset(asset_code.clone());
+    let legacy: LegacyAssetInfo = env.storage().instance().get(&key)?;
+    let asset = AssetInfo {
+        asset_code: legacy.asset_code,
+        issuer: legacy.issuer,
+        total_supply: legacy.total_supply,
+        description: legacy.description,
+        is_active: legacy.is_active,
+        registration_time: legacy.registration_time,
+        last_updated: legacy.registration_time,
+        is_verified: false,
+        description_locked: false,
+        metadata_uri: None,
+        successor: None,
+        issuer_verified: false,
+        category: None,
+        supply_known: true,
+        decimals: DEFAULT_DECIMALS,
+        reserved: false,
+        max_supply: None,
+    };
+    
+    env.storage().instance().remove(&key);
+    index_asset(env, asset_code);
+    write_asset(env, &asset);
+    let checkpoint = SupplyCheckpoint { timestamp: asset.registration_time, supply: asset.total_supply };
+    write_entry(env, &AssetBook::History(asset_code.clone()), &Vec::from_array(env, [checkpoint]));
+    add_issuer_asset(env, &asset.issuer, asset_code);
+    if asset.is_active {
+        track_active(env, &asset);
+    }
+    
+    log!(env, "Migrated asset to persistent storage: {}", asset_code);
+    Some(asset)
+}
+
+// Read the stored record for an asset code, panicking if it is missing
+fn load_asset(env: &Env, asset_code: &String) -> AssetInfo {
+    read_asset(env, asset_code).unwrap_or_else(|| {
+        log!(env, "Asset not found: {}", asset_code);
+        panic_with_error!(env, Error::AssetNotFound);
+    })
+}
+
+// Number of index slots handed out so far
+fn index_len(env: &Env) -> u64 {
+    env.storage().instance().get(&NEXT_INDEX).unwrap_or(0)
+}
+
+// Give a code the next free index slot
+fn index_asset(env: &Env, asset_code: &String) {
+    let index = index_len(env);
+    set_entry(env, &AssetBook::Index(index), asset_code);
+    set_entry(env, &AssetBook::IndexOf(asset_code.clone()), &index);
+    env.storage().instance().set(&NEXT_INDEX, &(index + 1));
+}
+
+// Index slots a scanning call resuming at start may read, at most MAX_SCAN of them
+fn scan_window(env: &Env, start: u64) -> core::ops::Range<u64> {
+    let end = start.saturating_add(MAX_SCAN).min(index_len(env));
+    start.min(end)..end
+}
+
+// Page size for a scanning call that stops once it has `limit` results: at least one,
+// so the returned slot always moves past start, and at most MAX_PAGE_SIZE
+fn scan_limit(limit: u32) -> u32 {
+    limit.clamp(1, MAX_PAGE_SIZE)
+}
+
+// Read the asset stored at an index slot, if the slot is still occupied
+fn read_asset_at(env: &Env, index: u64) -> Option<AssetInfo> {
+    let code: Option<String> = env.storage().persistent().get(&AssetBook::Index(index));
+    code.and_then(|code| read_asset(env, &code))
+}
+
+// Adjust a counter by one, never dropping below zero
+fn bump_counter(env: &Env, key: &Symbol, increment: bool) {
+    let count: u64 = env.storage().instance().get(key).unwrap_or(0);
+    let count = if increment { count.saturating_add(1) } else { count.saturating_sub(1) };
+    env.storage().instance().set(key, &count);
+}
+
+// Aggregate active supply. It is kept as an I256 so that no mix of i128 supplies
+// can overflow it; one issuer's huge supply must not block everyone else's writes.
+fn aggregate_supply(env: &Env) -> I256 {
+    env.storage().instance().get(&AGG_SUPPLY).unwrap_or(I256::from_i128(env, 0))
+}
+
+// Add a (possibly negative) change to the aggregate active supply
+fn adjust_aggregate(env: &Env, delta: i128) {
+    let total = aggregate_supply(env).add(&I256::from_i128(env, delta));
+    env.storage().instance().set(&AGG_SUPPLY, &total);
+}
+
+// Supply an asset contributes to aggregates; unknown supplies count as nothing
+fn counted_supply(asset: &AssetInfo) -> i128 {
+    if asset.supply_known { asset.total_supply } else { 0 }
+}
+
+// Count an asset in or out of its category, keeping the list of categories
+// in use limited to those with at least one active asset
+fn adjust_category(env: &Env, category: &Symbol, increment: bool) {
+    let key = AssetBook::CategoryCount(category.clone());
+    let count: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+    let count = if increment { count.saturating_add(1) } else { count.saturating_sub(1) };
+    
+    let mut categories: Vec<Symbol> = env.storage().instance().get(&CATEGORIES).unwrap_or(Vec::new(env));
+    let position = categories.first_index_of(category);
+    if count == 0 {
+        remove_entry(env, &key);
+        if let Some(position) = position {
+            categories.remove(position);
+        }
+    } else {
+        write_entry(env, &key, &count);
+        if position.is_none() {
+            categories.push_back(category.clone());
+        }
+    }
+    env.storage().instance().set(&CATEGORIES, &categories);
+}
+
+// Count a newly active asset towards the active counter, aggregate supply and its category
+fn track_active(env: &Env, asset: &AssetInfo) {
+    bump_counter(env, &ACTIVE_COUNT, true);
+    adjust_aggregate(env, counted_supply(asset));
+    if let Some(category) = &asset.category {
+        adjust_category(env, category, true);
+    }
+}
+
+// Remove a no longer active asset from the active counter, aggregate supply and its category
+fn untrack_active(env: &Env, asset: &AssetInfo) {
+    bump_counter(env, &ACTIVE_COUNT, false);
+    adjust_aggregate(env, -counted_supply(asset));
+    if let Some(category) = &asset.category {
+        adjust_category(env, category, false);
+    }
+}
+
+// Codes currently registered under an issuer, in registration order
+fn issuer_assets(env: &Env, issuer: &Address) -> Vec<String> {
+    env.storage().persistent().get(&AssetBook::Issuer(issuer.clone())).unwrap_or(Vec::new(env))
+}
+
+// Add a code to an issuer's list, ignoring codes already listed
+fn add_issuer_asset(env: &Env, issuer: &Address, asset_code: &String) {
+    let mut codes = issuer_assets(env, issuer);
+    if !codes.contains(asset_code) {
+        codes.push_back(asset_code.clone());
+        write_entry(env, &AssetBook::Issuer(issuer.clone()), &codes);
+    }
+}
+
+// Drop a code from an issuer's list, removing the list once it is empty
+fn remove_issuer_asset(env: &Env, issuer: &Address, asset_code: &String) {
+    let key = AssetBook::Issuer(issuer.clone());
+    let mut codes = issuer_assets(env, issuer);
+    if let Some(position) = codes.first_index_of(asset_code) {
+        codes.remove(position);
+        if codes.is_empty() {
+            remove_entry(env, &key);
+        } else {
+            write_entry(env, &key, &codes);
+        }
+    }
+}
+
+// Cross-check the counters and indexes against the stored records in the scan window
+// from start, returning the first invariant that fails and the slot to resume from.
+// The count checks need every slot, so they only run when one window covers the
+// whole index.
+fn find_invariant_violation(env: &Env, start: u64) -> (Option<Symbol>, u64) {
+    let storage = env.storage().instance();
+    let total: u64 = storage.get(&ASSET_COUNT).unwrap_or(0);
+    let active: u64 = storage.get(&ACTIVE_COUNT).unwrap_or(0);
+    let window = scan_window(env, start);
+    let next = window.end;
+    if active > total {
+        return (Some(symbol_short!("ACTIVE")), next);
+    }
+    
+    let mut records = 0;
+    let mut active_records = 0;
+    let mut supply = I256::from_i128(env, 0);
+    let whole_index = window.start == 0 && window.end == index_len(env);
+    for index in window {
+        let code: Option<String> = env.storage().persistent().get(&AssetBook::Index(index));
+        let Some(code) = code else {
+            continue;
+        };
+        
+        // Every occupied slot points back at itself and holds a live record
+        let Some(asset) = read_asset(env, &code) else {
+            return (Some(symbol_short!("INDEX")), next);
+        };
+        if env.storage().persistent().get::<_, u64>(&AssetBook::IndexOf(code.clone())) != Some(index) {
+            return (Some(symbol_short!("INDEX")), next);
+        }
+        if !issuer_assets(env, &asset.issuer).contains(&code) {
+            return (Some(symbol_short!("ISSUER")), next);
+        }
+        
+        records += 1;
+        if asset.is_active {
+            active_records += 1;
+            supply = supply.add(&I256::from_i128(env, counted_supply(&asset)));
+        }
+    }
+    
+    if whole_index {
+        if records != total {
+            return (Some(symbol_short!("COUNT")), next);
+        }
+        if active_records != active {
+            return (Some(symbol_short!("ACTIVE")), next);
+        }
+        if supply != aggregate_supply(env) {
+            return (Some(symbol_short!("SUPPLY")), next);
+        }
+    }
+    
+    (None, next)
+}
+
+// Hand an asset to a new issuer, moving it between the issuers' lists and dropping
+// the old issuer's delegate. The transfer event records whether the admin forced it.
+fn reassign_issuer(env: &Env, asset: &mut AssetInfo, new_issuer: &Address, by_admin: bool) {
+    if *new_issuer == sentinel_issuer(env) {
+        log!(env, "Invalid issuer: {}", new_issuer);
+        panic_with_error!(env, Error::InvalidIssuer);
+    }
+    
+    let asset_code = asset.asset_code.clone();
+    let old_issuer = asset.issuer.clone();
+    remove_issuer_asset(env, &old_issuer, &asset_code);
+    add_issuer_asset(env, new_issuer, &asset_code);
+    remove_entry(env, &AssetBook::Delegate(asset_code.clone()));
+    
+    asset.issuer = new_issuer.clone();
+    asset.last_updated = env.ledger().timestamp();
+    write_asset(env, asset);
+    bump_ttl(env);
+    
+    emit_event(env, symbol_short!("transfer"), &asset_code, (old_issuer, new_issuer.clone(), by_admin));
+}
+
+// Append a supply checkpoint to an asset's history
+fn record_supply(env: &Env, asset_code: &String, supply: i128) {
+    let key = AssetBook::History(asset_code.clone());
+    let mut history = supply_history(env, asset_code);
+    if history.len() >= MAX_HISTORY {
+        history.pop_front();
+        write_entry(env, &AssetBook::HistoryTruncated(asset_code.clone()), &true);
+    }
+    history.push_back(SupplyCheckpoint {
+        timestamp: env.ledger().timestamp(),
+        supply,
+    });
+    write_entry(env, &key, &history);
+}
+
+// An asset's retained supply checkpoints, oldest first
+fn supply_history(env: &Env, asset_code: &String) -> Vec<SupplyCheckpoint> {
+    env.storage().persistent().get(&AssetBook::History(asset_code.clone())).unwrap_or(Vec::new(env))
+}
+
+// Whether a time falls before the retained checkpoints of a history that has had
+// older ones dropped, so the supply in effect then is no longer known
+fn history_truncated_before(env: &Env, asset_code: &String, history: &Vec<SupplyCheckpoint>, timestamp: u64) -> bool {
+    history.first().is_some_and(|oldest| timestamp < oldest.timestamp)
+        && env.storage().persistent().has(&AssetBook::HistoryTruncated(asset_code.clone()))
+}
+
+// Time-weighted average of a supply history over [from, to], weighting time before
+// the first checkpoint at the earliest known supply. None without history, for a
+// reversed window, or if the weighted sum overflows.
+fn time_weighted_supply(history: &Vec<SupplyCheckpoint>, from: u64, to: u64) -> Option<i128> {
+    if history.is_empty() || from > to {
+        return None;
+    }
+    
+    // Supply in effect at the start of the window
+    let mut supply = history.get_unchecked(0).supply;
+    for checkpoint in history.iter() {
+        if checkpoint.timestamp > from {
+            break;
+        }
+        supply = checkpoint.supply;
+    }
+    if from == to {
+        return Some(supply);
+    }
+    
+    let mut weighted: i128 = 0;
+    let mut since = from;
+    for checkpoint in history.iter() {
+        if checkpoint.timestamp <= from {
+            continue;
+        }
+        if checkpoint.timestamp >= to {
+            break;
+        }
+        
+        let elapsed = (checkpoint.timestamp - since) as i128;
+        weighted = weighted.checked_add(supply.checked_mul(elapsed)?)?;
+        since = checkpoint.timestamp;
+        supply = checkpoint.supply;
+    }
+    
+    let elapsed = (to - since) as i128;
+    weighted = weighted.checked_add(supply.checked_mul(elapsed)?)?;
+    Some(weighted / (to - from) as i128)
+}
+
+// Whether an asset should be surfaced in public listings
+fn is_listed(env: &Env, asset: &AssetInfo) -> bool {
+    let require_verified: bool = env.storage().instance().get(&REQUIRE_VERIFIED).unwrap_or(false);
+    asset.is_active && (asset.is_verified || !require_verified)
+}
+
+// Longest raw code canonical_code will trim; longer inputs are left as they are
+// and fail validation anyway
+const MAX_RAW_CODE_LEN: u32 = 32;
+
+// Canonical form of a code used as a storage key, with surrounding ASCII
+// whitespace trimmed off
+fn canonical_code(env: &Env, asset_code: &String) -> String {
+    let len = asset_code.len();
+    if len > MAX_RAW_CODE_LEN {
+        return asset_code.clone();
+    }
+    
+    let mut buf = [0u8; MAX_RAW_CODE_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    asset_code.copy_into_slice(bytes);
+    String::from_bytes(env, bytes.trim_ascii())
+}
+
+// Whether a code is 1-12 ASCII letters or digits
+fn is_valid_code(asset_code: &String) -> bool {
+    let len = asset_code.len();
+    if len == 0 || len > MAX_CODE_LEN {
+        return false;
+    }
+    
+    let mut buf = [0u8; MAX_CODE_LEN as usize];
+    let bytes = &mut buf[..len as usize];
+    asset_code.copy_into_slice(bytes);
+    bytes.iter().all(|b| b.is_ascii_alphanumeric())
+}
+
+// Reject empty or oversized metadata URIs
+fn require_valid_uri(env: &Env, uri: &String) {
+    if uri.is_empty() || uri.len() > MAX_URI_LEN {
+        log!(env, "Invalid metadata URI length: {}", uri.len());
+        panic_with_error!(env, Error::InvalidMetadataUri);
+    }
+}
+
+// Whether two codes are equal ignoring ASCII case; codes longer than any
+// valid code never match
+fn codes_match_ci(a: &String, b: &String) -> bool {
+    let len = a.len();
+    if len != b.len() || len > MAX_CODE_LEN {
+        return false;
+    }
+    
+    let mut buf_a = [0u8; MAX_CODE_LEN as usize];
+    let mut buf_b = [0u8; MAX_CODE_LEN as usize];
+    a.copy_into_slice(&mut buf_a[..len as usize]);
+    b.copy_into_slice(&mut buf_b[..len as usize]);
+    buf_a.eq_ignore_ascii_case(&buf_b)
+}
+
+// Whether a supply satisfies the whole-units policy for the given decimals
+fn is_whole_units(env: &Env, supply: i128, decimals: u32) -> bool {
+    let whole_units_only: bool = env.storage().instance().get(&WHOLE_UNITS).unwrap_or(false);
+    !whole_units_only || supply % 10i128.pow(decimals) == 0
+}
+
+// Code of the placeholder record older versions returned for unknown assets;
+// still reserved so it never names a real asset
+const NOT_FOUND_CODE: &str = "NOT_FOUND";
+
+// Issuer of that placeholder record; still rejected as a real issuer
+fn sentinel_issuer(env: &Env) -> Address {
+    Address::from_string(&String::from_str(env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF"))
+}
+
+// Whether moving from old_supply to new_supply breaches the configured alert threshold;
+// any change away from a zero supply counts as a breach
+fn breaches_alert_threshold(env: &Env, old_supply: i128, new_supply: i128) -> bool {
+    let threshold: Option<u32> = env.storage().instance().get(&ALERT_BPS);
+    let Some(threshold) = threshold else {
+        return false;
+    };
+    
+    let change = new_supply.abs_diff(old_supply);
+    if change == 0 {
+        return false;
+    }
+    if old_supply == 0 {
+        return true;
+    }
+    
+    let old_supply = old_supply.unsigned_abs();
+    let bps = match change.checked_mul(10_000) {
+        Some(scaled) => scaled / old_supply,
+        None => change / (old_supply / 10_000).max(1),
+    };
+    bps > threshold as u128
+}
+
+// Whether a canonical code may name an asset. The sentinel's code already fails
+// validation, but is refused explicitly in any case so that a lookup miss can never
+// be confused with a real record.
+fn is_registrable_code(env: &Env, asset_code: &String) -> bool {
+    is_valid_code(asset_code) && !codes_match_ci(asset_code, &String::from_str(env, NOT_FOUND_CODE))
+}
+
+// Run every registration check without writing, returning the first failure
+fn check_registration(env: &Env, asset_code: &String, issuer: &Address, total_supply: i128) -> RegisterResult {
+    let asset_code = &canonical_code(env, asset_code);
+    if is_paused(env) {
+        return RegisterResult::Paused;
+    }
+    
+    if is_clearing(env) {
+        return RegisterResult::Clearing;
+    }
+    
+    if !is_registrable_code(env, asset_code) {
+        return RegisterResult::InvalidCode;
+    }
+    
+    if *issuer == sentinel_issuer(env) {
+        return RegisterResult::InvalidIssuer;
+    }
+    
+    if total_supply < 0 {
+        return RegisterResult::InvalidSupply;
+    }
+    if !is_whole_units(env, total_supply, DEFAULT_DECIMALS) {
+        return RegisterResult::FractionalSupply;
+    }
+    
+    // Reserved placeholders are inactive but still hold the code for their issuer, and
+    // without code reuse any inactive record does
+    let code_reuse: bool = env.storage().instance().get(&CODE_REUSE).unwrap_or(false);
+    if read_asset(env, asset_code).is_some_and(|asset| asset.is_active || asset.reserved || !code_reuse) {
+        return RegisterResult::AlreadyExists;
+    }
+    
+    let mode: RegistrationMode = env.storage().instance().get(&REG_MODE).unwrap_or(RegistrationMode::Open);
+    let allowed: bool = env.storage().persistent().get(&AssetBook::Allowed(issuer.clone())).unwrap_or(false);
+    if mode == RegistrationMode::Allowlist && !allowed {
+        return RegisterResult::Unauthorized;
+    }
+    
+    RegisterResult::Registered
+}
+
+// Run every registration check, returning the error for the first failure
+fn registration_error(env: &Env, asset_code: &String, issuer: &Address, total_supply: i128) -> Result<(), Error> {
+    match check_registration(env, asset_code, issuer, total_supply) {
+        RegisterResult::Registered => Ok(()),
+        RegisterResult::AlreadyExists => {
+            log!(env, "Asset already registered: {}", asset_code);
+            Err(Error::AssetAlreadyExists)
+        }
+        RegisterResult::InvalidCode => {
+            log!(env, "Invalid asset code: {}", asset_code);
+            Err(Error::InvalidCode)
+        }
+        RegisterResult::InvalidSupply => {
+            log!(env, "Invalid total supply: {}", total_supply);
+            Err(Error::InvalidSupply)
+        }
+        RegisterResult::FractionalSupply => {
+            log!(env, "Fractional supply for: {}", asset_code);
+            Err(Error::FractionalSupply)
+        }
+        RegisterResult::Unauthorized => {
+            log!(env, "Issuer not allowed to register: {}", issuer);
+            Err(Error::IssuerNotAllowed)
+        }
+        RegisterResult::Paused => Err(Error::Paused),
+        RegisterResult::InvalidIssuer => {
+            log!(env, "Invalid issuer: {}", issuer);
+            Err(Error::InvalidIssuer)
+        }
+        RegisterResult::Clearing => Err(Error::RegistryClearing),
+        RegisterResult::Conflict => Err(Error::DuplicateInBatch),
+        RegisterResult::InvalidDescription => Err(Error::DescriptionTooShort),
+    }
+}
+
+// Run every registration check, panicking with the error for the first failure
+fn require_registration(env: &Env, asset_code: &String, issuer: &Address, total_supply: i128) {
+    if let Err(error) = registration_error(env, asset_code, issuer, total_supply) {
+        panic_with_error!(env, error);
+    }
+}
+
+// Whether a description meets the admin's minimum length
+fn is_valid_description(env: &Env, description: &String) -> bool {
+    let min_len: u32 = env.storage().instance().get(&MIN_DESC_LEN).unwrap_or(0);
+    description.len() >= min_len
+}
+
+// Reject descriptions shorter than the admin's minimum length
+fn require_valid_description(env: &Env, description: &String) {
+    if !is_valid_description(env, description) {
+        log!(env, "Description too short: {} bytes", description.len());
+        panic_with_error!(env, Error::DescriptionTooShort);
+    }
+}
+
+// Free the index slot held by a code, if any
+fn unindex(env: &Env, asset_code: &String) {
+    let index_key = AssetBook::IndexOf(asset_code.clone());
+    if let Some(index) = env.storage().persistent().get::<_, u64>(&index_key) {
+        remove_entry(env, &AssetBook::Index(index));
+        remove_entry(env, &index_key);
+    }
+}
+
+// Delete an asset's record, index entries and per-asset data, updating counters
+fn delete_asset(env: &Env, asset: &AssetInfo) {
+    let asset_code = &asset.asset_code;
+    unindex(env, asset_code);
+    remove_entry(env, &AssetBook::Asset(asset_code.clone()));
+    remove_entry(env, &AssetBook::LiveUntil(asset_code.clone()));
+    remove_entry(env, &AssetBook::History(asset_code.clone()));
+    remove_entry(env, &AssetBook::HistoryTruncated(asset_code.clone()));
+    remove_entry(env, &AssetBook::Delegate(asset_code.clone()));
+    remove_entry(env, &AssetBook::PriceOracle(asset_code.clone()));
+    remove_issuer_asset(env, &asset.issuer, asset_code);
+    
+    bump_counter(env, &ASSET_COUNT, false);
+    if asset.is_active {
+        untrack_active(env, asset);
+    }
+}
+
+// Build the record for a newly registered asset with default flags. Registrations
+// are signed by the issuer, so records start out issuer-verified.
+fn new_asset_info(
+    env: &Env,
+    asset_code: &String,
+    issuer: &Address,
+    total_supply: i128,
+    description: String,
+) -> AssetInfo {
+    let time = env.ledger().timestamp();
+    
+    AssetInfo {
+        asset_code: canonical_code(env, asset_code),
+        issuer: issuer.clone(),
+        total_supply,
+        description,
+        is_active: true,
+        registration_time: time,
+        last_updated: time,
+        is_verified: false,
+        description_locked: false,
+        metadata_uri: None,
+        successor: None,
+        issuer_verified: true,
+        category: None,
+        supply_known: true,
+        decimals: DEFAULT_DECIMALS,
+        reserved: false,
+        max_supply: None,
+    }
+}
+
+// Write a freshly registered asset along with its index entries and counters
+fn store_new_asset(env: &Env, new_asset: &AssetInfo) {
+    let asset_code = &new_asset.asset_code;
+    
+    // An inactive record being claimed again is replaced outright, so none of
+    // its index slot, history or delegate carries over
+    if let Some(existing) = read_asset(env, asset_code) {
+        delete_asset(env, &existing);
+    }
+    
+    // Never resurrect a slot left behind without a record; the code always
+    // gets a fresh one
+    unindex(env, asset_code);
+    
+    // Index the new asset in the next free slot before storing it, so the write
+    // extends the index entries' lifetime along with the record's
+    index_asset(env, asset_code);
+    
+    // Store asset information, starting its supply history once supply is known,
+    // and update asset counts
+    write_asset(env, new_asset);
+    if new_asset.supply_known {
+        record_supply(env, asset_code, new_asset.total_supply);
+    }
+    add_issuer_asset(env, &new_asset.issuer, asset_code);
+    bump_counter(env, &ASSET_COUNT, true);
+    if new_asset.is_active {
+        track_active(env, new_asset);
+        let payload = (new_asset.issuer.clone(), new_asset.total_supply, new_asset.registration_time);
+        emit_event(env, symbol_short!("register"), asset_code, payload);
+    }
+    bump_ttl(env);
+}
+
+// Publish a state-change event under (topic, asset_code) with data (seq, payload),
+// unless the event level suppresses it. seq increases by one per published event
+// so consumers can detect gaps.
+// Every event also lands in the activity feed, whatever the event level.
+#[allow(deprecated)]
+fn emit_event<D: IntoVal<Env, Val>>(env: &Env, topic: Symbol, asset_code: &String, payload: D) {
+    record_activity(env, &topic, asset_code);
+    
+    let level: u32 = env.storage().instance().get(&EVENT_LEVEL).unwrap_or(EVENTS_ALL);
+    if level < EVENTS_STATE {
+        return;
+    }
+    
+    let seq: u64 = env.storage().instance().get(&EVENT_SEQ).unwrap_or(0);
+    let seq = seq + 1;
+    env.storage().instance().set(&EVENT_SEQ, &seq);
+    
+    let payload: Val = payload.into_val(env);
+    env.events().publish((topic, asset_code.clone()), (seq, payload));
+}
+
+// Append to the activity feed, dropping the oldest entry once it is full
+fn record_activity(env: &Env, kind: &Symbol, asset_code: &String) {
+    let mut activity: Vec<Activity> = env.storage().instance().get(&ACTIVITY).unwrap_or(Vec::new(env));
+    if activity.len() >= MAX_ACTIVITY {
+        activity.pop_front();
+    }
+    
+    activity.push_back(Activity {
+        kind: kind.clone(),
+        asset_code: asset_code.clone(),
+        timestamp: env.ledger().timestamp(),
+    });
+    env.storage().instance().set(&ACTIVITY, &activity);
+}
+
+// Read the TTL policy applied on writes
+fn ttl_config(env: &Env) -> TtlConfig {
+    env.storage().instance().get(&TTL_CONFIG).unwrap_or(TtlConfig {
+        enabled: true,
+        threshold: 5000,
+        extend_to: 5000,
+    })
+}
+
+// Reject TTL policies that could not be applied
+fn require_valid_ttl_config(env: &Env, config: &TtlConfig) {
+    if config.threshold > config.extend_to || config.extend_to > env.storage().max_ttl() {
+        log!(env, "Invalid TTL config: {} / {}", config.threshold, config.extend_to);
+        panic_with_error!(env, Error::InvalidTtlConfig);
+    }
+}
+
+// Extend the contract's storage lifetime according to the TTL policy
+fn bump_ttl(env: &Env) {
+    let config = ttl_config(env);
+    if config.enabled {
+        env.storage().instance().extend_ttl(config.threshold, config.extend_to);
+    }
+}
+
+// Store a persistent entry, counting it if it is new
+fn set_entry<V: IntoVal<Env, Val>>(env: &Env, key: &AssetBook, value: &V) {
+    if !env.storage().persistent().has(key) {
+        bump_counter(env, &ENTRY_COUNT, true);
+    }
+    env.storage().persistent().set(key, value);
+}
+
+// Remove a persistent entry, uncounting it if it existed
+fn remove_entry(env: &Env, key: &AssetBook) {
+    if env.storage().persistent().has(key) {
+        bump_counter(env, &ENTRY_COUNT, false);
+        env.storage().persistent().remove(key);
+    }
+}
+
+// Store a per-asset, per-issuer or per-category entry in persistent storage,
+// extending its lifetime according to the TTL policy
+fn write_entry<V: IntoVal<Env, Val>>(env: &Env, key: &AssetBook, value: &V) {
+    set_entry(env, key, value);
+    let config = ttl_config(env);
+    if config.enabled {
+        env.storage().persistent().extend_ttl(key, config.threshold, config.extend_to);
+    }
+}
+
+// Extend the lifetime of an asset's persistent entries according to the TTL policy.
+// Reads go through here, so it never writes; writes also record the new bound.
+fn bump_asset_ttl(env: &Env, asset: &AssetInfo) {
+    let config = ttl_config(env);
+    if config.enabled {
+        extend_asset_entries(env, asset, config.threshold, config.extend_to);
+    }
+}
+
+// Extend an asset's entries according to the TTL policy and record the new bound
+fn keep_asset_alive(env: &Env, asset: &AssetInfo) -> bool {
+    let config = ttl_config(env);
+    if config.enabled {
+        extend_asset_entries(env, asset, config.threshold, config.extend_to);
+        note_asset_ttl(env, &asset.asset_code, config.threshold, config.extend_to);
+    }
+    config.enabled
+}
+
+// Extend an asset's record, index entries, per-asset data and its issuer's list to
+// extend_to ledgers once they drop below threshold
+fn extend_asset_entries(env: &Env, asset: &AssetInfo, threshold: u32, extend_to: u32) {
+    let asset_code = &asset.asset_code;
+    let storage = env.storage().persistent();
+    storage.extend_ttl(&AssetBook::Asset(asset_code.clone()), threshold, extend_to);
+    
+    let index_key = AssetBook::IndexOf(asset_code.clone());
+    if let Some(index) = storage.get::<_, u64>(&index_key) {
+        storage.extend_ttl(&index_key, threshold, extend_to);
+        storage.extend_ttl(&AssetBook::Index(index), threshold, extend_to);
+    }
+    for key in [
+        AssetBook::History(asset_code.clone()),
+        AssetBook::HistoryTruncated(asset_code.clone()),
+        AssetBook::Delegate(asset_code.clone()),
+        AssetBook::PriceOracle(asset_code.clone()),
+        AssetBook::LiveUntil(asset_code.clone()),
+        AssetBook::Issuer(asset.issuer.clone()),
+    ] {
+        if storage.has(&key) {
+            storage.extend_ttl(&key, threshold, extend_to);
+        }
+    }
+}
+
+// Record a lower bound on the ledger an asset's entries live until, after extending
+// them with extend_asset_entries. Contracts can't read TTLs, so each extension notes
+// the ledger the entries are at least kept alive to: they either already had
+// `threshold` ledgers left or were extended to `extend_to`, which is no less.
+fn note_asset_ttl(env: &Env, asset_code: &String, threshold: u32, extend_to: u32) {
+    let storage = env.storage().persistent();
+    let key = AssetBook::LiveUntil(asset_code.clone());
+    let known: u32 = storage.get(&key).unwrap_or(0);
+    let live_until = env.ledger().sequence().saturating_add(threshold);
+    if live_until > known {
+        set_entry(env, &key, &live_until);
+        storage.extend_ttl(&key, threshold, extend_to);
+    }
+}
+
+// Whether the admin has paused the registry
+fn is_paused(env: &Env) -> bool {
+    env.storage().instance().get(&PAUSED).unwrap_or(false)
+}
+
+// Reject state changes while the registry is paused
+fn require_not_paused(env: &Env) {
+    if is_paused(env) {
+        panic_with_error!(env, Error::Paused);
+    }
+}
+
+// Whether clear_registry is part-way through deleting records
+fn is_clearing(env: &Env) -> bool {
+    env.storage().instance().get(&CLEARING).unwrap_or(false)
+}
+
+// Load the admin and require its authorization
+fn require_admin(env: &Env) -> Address {
+    let admin: Address = env.storage().instance().get(&ADMIN).unwrap_or_else(|| {
+        panic_with_error!(env, Error::NotInitialized);
+    });
+    admin.require_auth();
+    admin
+}
+
+#[contract]
+pub struct AssetExplorerContract;
+
+#[contractimpl]
+impl AssetExplorerContract {
+    
+    // Function to set the admin of a registry deployed before the constructor existed,
+    // allowed only once; newer deployments are initialized by the constructor
+    pub fn initialize(env: Env, admin: Address) -> bool {
+        if env.storage().instance().has(&ADMIN) {
+            panic_with_error!(&env, Error::AlreadyInitialized);
+        }
+        
+        env.storage().instance().set(&ADMIN, &admin);
+        bump_ttl(&env);
+        
+        log!(&env, "Registry initialized with admin: {}", admin);
+        true
+    }
+    
+    // Constructor to set the admin, schema version, TTL policy and registration mode as
+    // part of deployment, so the registry is never left half-configured. Soroban runs
+    // it exactly once, when the contract is created.
+    pub fn __constructor(env: Env, admin: Address, config: RegistryConfig) {
+        require_valid_ttl_config(&env, &config.ttl);
+        
+        env.storage().instance().set(&ADMIN, &admin);
+        env.storage().instance().set(&VERSION, &SCHEMA_VERSION);
+        env.storage().instance().set(&TTL_CONFIG, &config.ttl);
+        env.storage().instance().set(&REG_MODE, &config.registration_mode);
+        bump_ttl(&env);
+        
+        log!(&env, "Registry constructed with admin: {}", admin);
+    }
+    
+    // Function to get the storage layout version, or 0 if the registry predates versioning
+    pub fn get_schema_version(env: Env) -> u32 {
+        env.storage().instance().get(&VERSION).unwrap_or(0)
+    }
+    
+    // Function to halt asset changes until unpaused (admin only)
+    pub fn pause(env: Env) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&PAUSED, &true);
+        bump_ttl(&env);
+        
+        log!(&env, "Registry paused");
+        true
+    }
+    
+    // Function to resume asset changes (admin only)
+    pub fn unpause(env: Env) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&PAUSED, &false);
+        bump_ttl(&env);
+        
+        log!(&env, "Registry unpaused");
+        true
+    }
+    
+    // Function to change how far storage lifetime is extended on writes (admin only)
+    pub fn set_ttl_config(env: Env, config: TtlConfig) -> bool {
+        require_admin(&env);
+        require_valid_ttl_config(&env, &config);
+        
+        env.storage().instance().set(&TTL_CONFIG, &config);
+        bump_ttl(&env);
+        
+        log!(&env, "TTL config updated");
+        true
+    }
+    
+    // Function to choose how many events the contract publishes (admin only)
+    pub fn set_event_level(env: Env, level: u32) -> bool {
+        require_admin(&env);
+        
+        if !matches!(level, EVENTS_NONE | EVENTS_STATE | EVENTS_ALL) {
+            log!(&env, "Invalid event level: {}", level);
+            panic_with_error!(&env, Error::InvalidEventLevel);
+        }
+        
+        env.storage().instance().set(&EVENT_LEVEL, &level);
+        bump_ttl(&env);
+        
+        log!(&env, "Event level set to: {}", level);
+        true
+    }
+    
+    // Function to get the current event level
+    pub fn get_event_level(env: Env) -> u32 {
+        env.storage().instance().get(&EVENT_LEVEL).unwrap_or(EVENTS_ALL)
+    }
+    
+    // Function to get the TTL policy applied on writes
+    pub fn get_ttl_config(env: Env) -> TtlConfig {
+        ttl_config(&env)
+    }
+    
+    // Function to report whether the registry is initialized and whether it is paused
+    pub fn health(env: Env) -> (bool, bool) {
+        (env.storage().instance().has(&ADMIN), is_paused(&env))
+    }
+    
+    // Function to register a new asset on the explorer
+    pub fn register_asset(
+        env: Env, 
+        asset_code: String, 
+        issuer: Address,
+        total_supply: i128,
+        description: String
+    ) -> Result<(), Error> {
+        let asset_code = canonical_code(&env, &asset_code);
+        issuer.require_auth();
+        registration_error(&env, &asset_code, &issuer, total_supply)?;
+        if !is_valid_description(&env, &description) {
+            log!(&env, "Description too short: {} bytes", description.len());
+            return Err(Error::DescriptionTooShort);
+        }
+        store_new_asset(&env, &new_asset_info(&env, &asset_code, &issuer, total_supply, description));
+        
+        log!(&env, "Asset registered successfully: {}", asset_code);
+        Ok(())
+    }
+    
+    // Function to register an asset whose supply is not known yet; it stays out of
+    // supply aggregates until update_asset_supply sets one
+    pub fn register_asset_pending_supply(
+        env: Env,
+        asset_code: String,
+        issuer: Address,
+        description: String
+    ) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        issuer.require_auth();
+        require_registration(&env, &asset_code, &issuer, 0);
+        require_valid_description(&env, &description);
+        let mut new_asset = new_asset_info(&env, &asset_code, &issuer, 0, description);
+        new_asset.supply_known = false;
+        store_new_asset(&env, &new_asset);
+        
+        log!(&env, "Asset registered with pending supply: {}", asset_code);
+        true
+    }
+    
+    // Function to register an asset, reporting failures instead of panicking
+    pub fn register_asset_checked(
+        env: Env,
+        asset_code: String,
+        issuer: Address,
+        total_supply: i128,
+        description: String
+    ) -> RegisterResult {
+        let asset_code = canonical_code(&env, &asset_code);
+        issuer.require_auth();
+        let result = match check_registration(&env, &asset_code, &issuer, total_supply) {
+            RegisterResult::Registered if !is_valid_description(&env, &description) => RegisterResult::InvalidDescription,
+            result => result,
+        };
+        
+        if result == RegisterResult::Registered {
+            store_new_asset(&env, &new_asset_info(&env, &asset_code, &issuer, total_supply, description));
+            log!(&env, "Asset registered successfully: {}", asset_code);
+        } else {
+            log!(&env, "Asset not registered: {}", asset_code);
+        }
+        
+        result
+    }
+    
+    // Function to register several assets for one issuer, reporting each entry's
+    // outcome instead of panicking. Any repeat of a code already seen earlier in the
+    // batch is skipped as a Conflict, whatever happened to the first occurrence.
+    pub fn register_assets_batch(env: Env, issuer: Address, entries: Vec<(String, i128, String)>) -> Vec<RegisterResult> {
+        issuer.require_auth();
+        
+        let mut results = Vec::new(&env);
+        let mut seen: Map<String, bool> = Map::new(&env);
+        
+        for (asset_code, total_supply, description) in entries.iter() {
+            let canonical = canonical_code(&env, &asset_code);
+            if seen.contains_key(canonical.clone()) {
+                results.push_back(RegisterResult::Conflict);
+                continue;
+            }
+            seen.set(canonical, true);
+            
+            let result = match check_registration(&env, &asset_code, &issuer, total_supply) {
+                RegisterResult::Registered if !is_valid_description(&env, &description) => RegisterResult::InvalidDescription,
+                result => result,
+            };
+            if result == RegisterResult::Registered {
+                store_new_asset(&env, &new_asset_info(&env, &asset_code, &issuer, total_supply, description));
+            }
+            results.push_back(result);
+        }
+        
+        log!(&env, "Batch registration processed {} entries", results.len());
+        results
+    }
+    
+    // Function to register an asset so that retries are safe: repeating a call with the
+    // same issuer, key and code returns the earlier success without writing anything.
+    // Keys are remembered for IDEMPOTENCY_TTL ledgers.
+    pub fn register_asset_idempotent(
+        env: Env,
+        asset_code: String,
+        issuer: Address,
+        total_supply: i128,
+        description: String,
+        idempotency_key: BytesN<32>
+    ) -> bool {
+        issuer.require_auth();
+        let asset_code = canonical_code(&env, &asset_code);
+        let key = AssetBook::Idempotency(issuer.clone(), idempotency_key);
+        if let Some(registered_code) = env.storage().temporary().get::<_, String>(&key) {
+            if registered_code != asset_code {
+                log!(&env, "Idempotency key already used for: {}", registered_code);
+                panic_with_error!(&env, Error::IdempotencyKeyUsed);
+            }
+            
+            // The key only vouches for a record the issuer still holds; once that is
+            // removed a retry registers afresh
+            if read_asset(&env, &asset_code).is_some_and(|asset| asset.issuer == issuer) {
+                log!(&env, "Repeated registration ignored: {}", asset_code);
+                return true;
+            }
+        }
+        
+        require_registration(&env, &asset_code, &issuer, total_supply);
+        require_valid_description(&env, &description);
+        store_new_asset(&env, &new_asset_info(&env, &asset_code, &issuer, total_supply, description));
+        env.storage().temporary().set(&key, &asset_code);
+        env.storage().temporary().extend_ttl(&key, IDEMPOTENCY_TTL, IDEMPOTENCY_TTL);
+        
+        log!(&env, "Asset registered successfully: {}", asset_code);
+        true
+    }
+    
+    // Function for a trusted registrar to register an asset that is verified from the start
+    pub fn register_verified_asset(
+        env: Env,
+        registrar: Address,
+        asset_code: String,
+        issuer: Address,
+        total_supply: i128,
+        description: String
+    ) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        registrar.require_auth();
+        if !env.storage().persistent().has(&AssetBook::Registrar(registrar.clone())) {
+            log!(&env, "Not a registrar: {}", registrar);
+            panic_with_error!(&env, Error::NotRegistrar);
+        }
+        
+        require_registration(&env, &asset_code, &issuer, total_supply);
+        require_valid_description(&env, &description);
+        // The registrar vouches for the asset, but the issuer never signed
+        let mut new_asset = new_asset_info(&env, &asset_code, &issuer, total_supply, description);
+        new_asset.is_verified = true;
+        new_asset.issuer_verified = false;
+        store_new_asset(&env, &new_asset);
+        
+        log!(&env, "Verified asset registered by {}: {}", registrar, asset_code);
+        true
+    }
+    
+    // Function to reserve an asset code for an issuer; the placeholder stays inactive
+    // and blocks other registrations until the issuer activates it
+    pub fn reserve_registration(env: Env, asset_code: String, issuer: Address) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        issuer.require_auth();
+        require_registration(&env, &asset_code, &issuer, 0);
+        
+        let mut placeholder = new_asset_info(&env, &asset_code, &issuer, 0, String::from_str(&env, ""));
+        placeholder.is_active = false;
+        placeholder.supply_known = false;
+        placeholder.reserved = true;
+        store_new_asset(&env, &placeholder);
+        
+        emit_event(&env, symbol_short!("reserve"), &asset_code, issuer);
+        log!(&env, "Asset code reserved: {}", asset_code);
+        true
+    }
+    
+    // Function to complete a reserved registration (reserving issuer only). The asset
+    // counts as registered from activation, and keeps the index slot of its reservation.
+    pub fn activate_registration(env: Env, asset_code: String, total_supply: i128, description: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        if is_clearing(&env) {
+            panic_with_error!(&env, Error::RegistryClearing);
+        }
+        let mut asset = load_asset(&env, &asset_code);
+        
+        if !asset.reserved {
+            log!(&env, "Asset not reserved: {}", asset_code);
+            panic_with_error!(&env, Error::NotReserved);
+        }
+        asset.issuer.require_auth();
+        
+        if total_supply < 0 {
+            log!(&env, "Invalid total supply: {}", total_supply);
+            panic_with_error!(&env, Error::InvalidSupply);
+        }
+        if !is_whole_units(&env, total_supply, asset.decimals) {
+            log!(&env, "Fractional supply for: {}", asset_code);
+            panic_with_error!(&env, Error::FractionalSupply);
+        }
+        require_valid_description(&env, &description);
+        
+        let time = env.ledger().timestamp();
+        asset.total_supply = total_supply;
+        asset.description = description;
+        asset.is_active = true;
+        asset.reserved = false;
+        asset.supply_known = true;
+        asset.issuer_verified = true;
+        asset.registration_time = time;
+        asset.last_updated = time;
+        
+        write_asset(&env, &asset);
+        record_supply(&env, &asset_code, total_supply);
+        track_active(&env, &asset);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("register"), &asset_code, (asset.issuer.clone(), total_supply, time));
+        log!(&env, "Reserved asset activated: {}", asset_code);
+        true
+    }
+    
+    // Function to check whether a registration would succeed, without writing anything
+    pub fn can_register(env: Env, asset_code: String, issuer: Address, total_supply: i128) -> RegisterResult {
+        check_registration(&env, &canonical_code(&env, &asset_code), &issuer, total_supply)
+    }
+    
+    // Function to choose who may register assets (admin only)
+    pub fn set_registration_mode(env: Env, mode: RegistrationMode) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&REG_MODE, &mode);
+        bump_ttl(&env);
+        
+        log!(&env, "Registration mode updated");
+        true
+    }
+    
+    // Function to add or remove an issuer from the registration allowlist (admin only)
+    pub fn set_issuer_allowed(env: Env, issuer: Address, allowed: bool) -> bool {
+        require_admin(&env);
+        
+        let key = AssetBook::Allowed(issuer.clone());
+        if allowed {
+            write_entry(&env, &key, &true);
+        } else {
+            remove_entry(&env, &key);
+        }
+        bump_ttl(&env);
+        
+        log!(&env, "Issuer allowlist updated for: {}", issuer);
+        true
+    }
+    
+    // Function to let an address register pre-verified assets (admin only)
+    pub fn add_registrar(env: Env, registrar: Address) -> bool {
+        require_admin(&env);
+        
+        write_entry(&env, &AssetBook::Registrar(registrar.clone()), &true);
+        bump_ttl(&env);
+        
+        log!(&env, "Registrar added: {}", registrar);
+        true
+    }
+    
+    // Function to revoke an address's registrar role (admin only)
+    pub fn remove_registrar(env: Env, registrar: Address) -> bool {
+        require_admin(&env);
+        
+        remove_entry(&env, &AssetBook::Registrar(registrar.clone()));
+        bump_ttl(&env);
+        
+        log!(&env, "Registrar removed: {}", registrar);
+        true
+    }
+    
+    // Function to raise or lower an issuer's reputation score (admin only)
+    pub fn adjust_reputation(env: Env, issuer: Address, delta: i32) -> i32 {
+        require_admin(&env);
+        
+        let score = Self::get_reputation(env.clone(), issuer.clone())
+            .saturating_add(delta)
+            .clamp(MIN_REPUTATION, MAX_REPUTATION);
+        
+        write_entry(&env, &AssetBook::Reputation(issuer.clone()), &score);
+        bump_ttl(&env);
+        
+        log!(&env, "Reputation for {} is now {}", issuer, score);
+        score
+    }
+    
+    // Function to get an issuer's reputation score
+    pub fn get_reputation(env: Env, issuer: Address) -> i32 {
+        env.storage().persistent().get(&AssetBook::Reputation(issuer)).unwrap_or(0)
+    }
+    
+    // Function to get the codes an issuer has registered, in registration order
+    pub fn get_assets_by_issuer(env: Env, issuer: Address) -> Vec<String> {
+        issuer_assets(&env, &issuer)
+    }
+    
+    // Function to get how many assets an issuer has registered
+    pub fn get_issuer_asset_count(env: Env, issuer: Address) -> u32 {
+        issuer_assets(&env, &issuer).len()
+    }
+    
+    // Function to get an issuer's asset counts, active supply and reputation in one call
+    pub fn get_issuer_summary(env: Env, issuer: Address) -> IssuerSummary {
+        let codes = issuer_assets(&env, &issuer);
+        let mut active_count = 0;
+        let mut aggregate_supply: i128 = 0;
+        
+        for code in codes.iter() {
+            if let Some(asset) = read_asset(&env, &code) {
+                if asset.is_active {
+                    active_count += 1;
+                    aggregate_supply = aggregate_supply.saturating_add(counted_supply(&asset));
+                }
+            }
+        }
+        
+        IssuerSummary {
+            asset_count: codes.len() as u64,
+            active_count,
+            aggregate_supply,
+            reputation: Self::get_reputation(env.clone(), issuer),
+        }
+    }
+    
+    // Function to register an asset using the configured default description
+    pub fn register_asset_minimal(
+        env: Env,
+        asset_code: String,
+        issuer: Address,
+        total_supply: i128
+    ) -> Result<(), Error> {
+        let description = Self::get_default_description(env.clone());
+        Self::register_asset(env, asset_code, issuer, total_supply, description)
+    }
+    
+    // Function to change the default description (admin only)
+    pub fn set_default_description(env: Env, description: String) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&DEFAULT_DESC, &description);
+        bump_ttl(&env);
+        
+        log!(&env, "Default description updated");
+        true
+    }
+    
+    // Function to get the description applied by register_asset_minimal
+    pub fn get_default_description(env: Env) -> String {
+        env.storage().instance().get(&DEFAULT_DESC)
+            .unwrap_or(String::from_str(&env, "No description provided"))
+    }
+    
+    // Function to brand the registry with a name and description (admin only)
+    pub fn set_registry_info(env: Env, name: String, description: String) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&REGISTRY_INFO, &(name.clone(), description));
+        bump_ttl(&env);
+        
+        log!(&env, "Registry info updated: {}", name);
+        true
+    }
+    
+    // Function to get the registry name and description
+    pub fn get_registry_info(env: Env) -> (String, String) {
+        env.storage().instance().get(&REGISTRY_INFO)
+            .unwrap_or((String::from_str(&env, ""), String::from_str(&env, "")))
+    }
+    
+    // Function to retrieve asset information by asset code
+    pub fn get_asset_info(env: Env, asset_code: String) -> Option<AssetInfo> {
+        read_asset(&env, &canonical_code(&env, &asset_code))
+    }
+    
+    // Function to get just an asset's description
+    pub fn get_description(env: Env, asset_code: String) -> Option<String> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| asset.description)
+    }
+    
+    // Function to get just an asset's total supply
+    pub fn get_total_supply(env: Env, asset_code: String) -> Option<i128> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| asset.total_supply)
+    }
+    
+    // Function to get just whether an asset is active
+    pub fn get_is_active(env: Env, asset_code: String) -> Option<bool> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| asset.is_active)
+    }
+    
+    // Function to get an asset's record as raw bytes for off-chain caching. The bytes
+    // are the XDR encoding of the record's ScVal (an ScMap keyed by field name), the
+    // same form it takes in contract storage.
+    pub fn get_asset_raw(env: Env, asset_code: String) -> Option<Bytes> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| asset.to_xdr(&env))
+    }
+    
+    // Function to get the sha256 of an asset's raw record, so caches can detect
+    // drift without fetching the record itself
+    pub fn get_asset_hash(env: Env, asset_code: String) -> Option<BytesN<32>> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| env.crypto().sha256(&asset.to_xdr(&env)).into())
+    }
+    
+    // Function to look up an asset by code, falling back to a case-insensitive match.
+    // The fallback reads up to MAX_SCAN index slots from start, so callers page with
+    // the returned slot until a match is found or the index is exhausted; exact
+    // codes stay a single read and end the search.
+    pub fn get_asset_info_ci(env: Env, asset_code: String, start: u64) -> (Option<AssetInfo>, u64) {
+        let asset_code = canonical_code(&env, &asset_code);
+        if let Some(asset) = read_asset(&env, &asset_code) {
+            return (Some(asset), index_len(&env));
+        }
+        
+        let window = scan_window(&env, start);
+        let next = window.end;
+        for index in window {
+            let code: Option<String> = env.storage().persistent().get(&AssetBook::Index(index));
+            if let Some(code) = code {
+                if codes_match_ci(&code, &asset_code) {
+                    return (read_asset(&env, &code), index + 1);
+                }
+            }
+        }
+        
+        (None, next)
+    }
+    
+    // Function to update asset supply, callable by the issuer or its delegate
+    pub fn update_asset_supply(env: Env, caller: Address, asset_code: String, new_supply: i128) -> Result<(), Error> {
+        let asset_code = canonical_code(&env, &asset_code);
+        if is_paused(&env) {
+            return Err(Error::Paused);
+        }
+        let Some(mut asset) = read_asset(&env, &asset_code) else {
+            log!(&env, "Asset not found: {}", asset_code);
+            return Err(Error::AssetNotFound);
+        };
+        if !asset.is_active {
+            log!(&env, "Asset not active: {}", asset_code);
+            return Err(Error::AssetInactive);
+        }
+        
+        caller.require_auth();
+        if caller != asset.issuer && Some(caller) != Self::get_delegate(env.clone(), asset_code.clone()) {
+            log!(&env, "Caller may not update asset: {}", asset_code);
+            return Err(Error::NotIssuerOrDelegate);
+        }
+        
+        if new_supply < 0 {
+            log!(&env, "Invalid total supply: {}", new_supply);
+            return Err(Error::InvalidSupply);
+        }
+        if !is_whole_units(&env, new_supply, asset.decimals) {
+            log!(&env, "Fractional supply for: {}", asset_code);
+            return Err(Error::FractionalSupply);
+        }
+        if asset.max_supply.is_some_and(|max_supply| new_supply > max_supply) {
+            log!(&env, "Supply above cap for: {}", asset_code);
+            return Err(Error::SupplyAboveMax);
+        }
+        
+        let delta = new_supply.checked_sub(counted_supply(&asset)).ok_or(Error::SupplyOverflow)?;
+        let old_supply = asset.total_supply;
+        let alert = asset.supply_known && breaches_alert_threshold(&env, old_supply, new_supply);
+        asset.total_supply = new_supply;
+        asset.supply_known = true;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        record_supply(&env, &asset_code, new_supply);
+        adjust_aggregate(&env, delta);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("supply"), &asset_code, (old_supply, new_supply, asset.last_updated));
+        if alert {
+            emit_event(&env, symbol_short!("alert"), &asset_code, (old_supply, new_supply));
+        }
+        log!(&env, "Asset supply updated for: {}", asset_code);
+        Ok(())
+    }
+    
+    // Function to delete an asset and free its index slot (issuer only)
+    pub fn remove_asset(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        delete_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("remove"), &asset_code, asset.issuer);
+        log!(&env, "Asset removed: {}", asset_code);
+        true
+    }
+    
+    // Function to retire an asset in favour of a replacement code (issuer only)
+    pub fn deprecate_asset(env: Env, asset_code: String, successor_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        let successor_code = canonical_code(&env, &successor_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        if !asset.is_active {
+            log!(&env, "Asset already inactive: {}", asset_code);
+            panic_with_error!(&env, Error::AssetInactive);
+        }
+        if !is_registrable_code(&env, &successor_code) {
+            log!(&env, "Invalid asset code: {}", successor_code);
+            panic_with_error!(&env, Error::InvalidCode);
+        }
+        if successor_code == asset_code {
+            panic_with_error!(&env, Error::InvalidSuccessor);
+        }
+        
+        asset.is_active = false;
+        asset.successor = Some(successor_code.clone());
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        untrack_active(&env, &asset);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("deprecate"), &asset_code, successor_code.clone());
+        log!(&env, "Asset {} deprecated in favour of {}", asset_code, successor_code);
+        true
+    }
+    
+    // Function to zero an asset's supply and deactivate it in one step (issuer only)
+    pub fn retire_asset(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        if !asset.is_active {
+            log!(&env, "Asset already inactive: {}", asset_code);
+            panic_with_error!(&env, Error::AssetInactive);
+        }
+        
+        untrack_active(&env, &asset);
+        let old_supply = asset.total_supply;
+        asset.total_supply = 0;
+        asset.supply_known = true;
+        asset.is_active = false;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        record_supply(&env, &asset_code, 0);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("supply"), &asset_code, (old_supply, 0i128, asset.last_updated));
+        emit_event(&env, symbol_short!("retire"), &asset_code, asset.issuer);
+        log!(&env, "Asset retired: {}", asset_code);
+        true
+    }
+    
+    // Function to get the replacement code of a deprecated asset
+    pub fn get_successor(env: Env, asset_code: String) -> Option<String> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).and_then(|asset| asset.successor)
+    }
+    
+    // Function to resync the active counter, aggregate supply and per-category counts
+    // from the records of the given codes (admin only)
+    pub fn recompute_active_count(env: Env, codes: Vec<String>) -> u64 {
+        require_admin(&env);
+        
+        // Count each code once, even if the caller repeats it
+        let mut seen: Map<String, bool> = Map::new(&env);
+        let mut supply = I256::from_i128(&env, 0);
+        let mut category_counts: Map<Symbol, u32> = Map::new(&env);
+        for code in codes.iter() {
+            let Some(asset) = read_asset(&env, &canonical_code(&env, &code)) else {
+                continue;
+            };
+            if !asset.is_active || seen.contains_key(asset.asset_code.clone()) {
+                continue;
+            }
+            
+            seen.set(asset.asset_code.clone(), true);
+            supply = supply.add(&I256::from_i128(&env, counted_supply(&asset)));
+            if let Some(category) = asset.category {
+                let count = category_counts.get(category.clone()).unwrap_or(0);
+                category_counts.set(category, count + 1);
+            }
+        }
+        
+        // Replace the per-category counts and the list of categories in use
+        let stale: Vec<Symbol> = env.storage().instance().get(&CATEGORIES).unwrap_or(Vec::new(&env));
+        for category in stale.iter() {
+            remove_entry(&env, &AssetBook::CategoryCount(category));
+        }
+        for (category, count) in category_counts.iter() {
+            write_entry(&env, &AssetBook::CategoryCount(category), &count);
+        }
+        
+        let count = seen.len() as u64;
+        env.storage().instance().set(&ACTIVE_COUNT, &count);
+        env.storage().instance().set(&AGG_SUPPLY, &supply);
+        env.storage().instance().set(&CATEGORIES, &category_counts.keys());
+        bump_ttl(&env);
+        
+        log!(&env, "Active asset count recomputed: {}", count);
+        count
+    }
+    
+    // Function to change an asset's description (issuer only, until locked)
+    pub fn update_description(env: Env, asset_code: String, description: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        if asset.description_locked {
+            log!(&env, "Description locked for: {}", asset_code);
+            panic_with_error!(&env, Error::DescriptionLocked);
+        }
+        require_valid_description(&env, &description);
+        
+        asset.description = description;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Description updated for: {}", asset_code);
+        true
+    }
+    
+    // Function to permanently freeze an asset's description (issuer only)
+    pub fn lock_description(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        asset.description_locked = true;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Description locked for: {}", asset_code);
+        true
+    }
+    
+    // Function to point an asset at off-chain metadata (issuer only)
+    pub fn set_metadata_uri(env: Env, asset_code: String, uri: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        require_valid_uri(&env, &uri);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        asset.metadata_uri = Some(uri);
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Metadata URI updated for: {}", asset_code);
+        true
+    }
+    
+    // Function to set metadata URIs for many of an issuer's assets at once,
+    // skipping codes the issuer does not own; returns how many were updated
+    pub fn set_metadata_uris_batch(env: Env, issuer: Address, updates: Vec<(String, String)>) -> u32 {
+        require_not_paused(&env);
+        issuer.require_auth();
+        
+        let mut updated = 0;
+        let time = env.ledger().timestamp();
+        
+        for (asset_code, uri) in updates.iter() {
+            require_valid_uri(&env, &uri);
+            
+            let mut asset = match read_asset(&env, &canonical_code(&env, &asset_code)) {
+                Some(asset) if asset.issuer == issuer => asset,
+                _ => continue,
+            };
+            
+            asset.metadata_uri = Some(uri);
+            asset.last_updated = time;
+            write_asset(&env, &asset);
+            updated += 1;
+        }
+        
+        bump_ttl(&env);
+        
+        log!(&env, "Metadata URIs updated: {}", updated);
+        updated
+    }
+    
+    // Function to declare how many decimal places an asset's supply uses (issuer only)
+    pub fn set_decimals(env: Env, asset_code: String, decimals: u32) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        if decimals > MAX_DECIMALS {
+            log!(&env, "Too many decimals: {}", decimals);
+            panic_with_error!(&env, Error::InvalidDecimals);
+        }
+        if asset.supply_known && !is_whole_units(&env, asset.total_supply, decimals) {
+            log!(&env, "Fractional supply for: {}", asset_code);
+            panic_with_error!(&env, Error::FractionalSupply);
+        }
+        
+        asset.decimals = decimals;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Decimals set for: {}", asset_code);
+        true
+    }
+    
+    // Function to cap an asset's supply, or lift the cap with None (issuer only)
+    pub fn set_max_supply(env: Env, asset_code: String, max_supply: Option<i128>) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        if let Some(max_supply) = max_supply {
+            if asset.supply_known && asset.total_supply > max_supply {
+                log!(&env, "Cap below current supply for: {}", asset_code);
+                panic_with_error!(&env, Error::SupplyAboveMax);
+            }
+        }
+        
+        asset.max_supply = max_supply;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Max supply set for: {}", asset_code);
+        true
+    }
+    
+    // Function to tell whether an asset has a fixed maximum supply
+    pub fn is_supply_capped(env: Env, asset_code: String) -> Option<bool> {
+        read_asset(&env, &canonical_code(&env, &asset_code)).map(|asset| asset.max_supply.is_some())
+    }
+    
+    // Function to require supplies to be whole units of each asset (admin only)
+    pub fn set_whole_units_only(env: Env, enabled: bool) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&WHOLE_UNITS, &enabled);
+        bump_ttl(&env);
+        
+        log!(&env, "Whole-units policy set to: {}", enabled);
+        true
+    }
+    
+    // Function to set the supply change, in basis points, that triggers an alert event (admin only)
+    pub fn set_alert_threshold_bps(env: Env, threshold_bps: u32) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&ALERT_BPS, &threshold_bps);
+        bump_ttl(&env);
+        
+        log!(&env, "Supply alert threshold set to: {}", threshold_bps);
+        true
+    }
+    
+    // Function to set the minimum description length in bytes, or 0 to disable it (admin only)
+    pub fn set_min_description_len(env: Env, min_len: u32) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&MIN_DESC_LEN, &min_len);
+        bump_ttl(&env);
+        
+        log!(&env, "Minimum description length set to: {}", min_len);
+        true
+    }
+    
+    // Function to choose whether inactive codes may be registered again (admin only)
+    pub fn set_allow_code_reuse(env: Env, allowed: bool) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&CODE_REUSE, &allowed);
+        bump_ttl(&env);
+        
+        log!(&env, "Code reuse set to: {}", allowed);
+        true
+    }
+    
+    // Function to file an asset under a category (issuer only)
+    pub fn set_category(env: Env, asset_code: String, category: Symbol) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        // Only active assets count towards the categories in use
+        if asset.is_active && asset.category.as_ref() != Some(&category) {
+            if let Some(previous) = &asset.category {
+                adjust_category(&env, previous, false);
+            }
+            adjust_category(&env, &category, true);
+        }
+        
+        asset.category = Some(category);
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Category set for: {}", asset_code);
+        true
+    }
+    
+    // Function to list the categories that hold at least one active asset
+    pub fn get_categories(env: Env) -> Vec<Symbol> {
+        env.storage().instance().get(&CATEGORIES).unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to let an operator manage supply on the issuer's behalf
+    pub fn set_delegate(env: Env, asset_code: String, delegate: Address) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        write_entry(&env, &AssetBook::Delegate(asset_code.clone()), &delegate);
+        bump_ttl(&env);
+        
+        log!(&env, "Delegate set for: {}", asset_code);
+        true
+    }
+    
+    // Function to revoke the delegate of an asset
+    pub fn remove_delegate(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        remove_entry(&env, &AssetBook::Delegate(asset_code.clone()));
+        bump_ttl(&env);
+        
+        log!(&env, "Delegate removed for: {}", asset_code);
+        true
+    }
+    
+    // Function to set or clear the price oracle used to value an asset (admin only)
+    pub fn set_price_oracle(env: Env, asset_code: String, oracle: Option<Address>) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_admin(&env);
+        load_asset(&env, &asset_code);
+        
+        let key = AssetBook::PriceOracle(asset_code.clone());
+        match oracle {
+            Some(oracle) => write_entry(&env, &key, &oracle),
+            None => remove_entry(&env, &key),
+        }
+        bump_ttl(&env);
+        
+        log!(&env, "Price oracle updated for: {}", asset_code);
+        true
+    }
+    
+    // Function to value an asset's supply at its oracle price, i.e. total_supply / 10^decimals
+    // whole units times the price of one unit. The product is taken at 256 bits, so only
+    // a value that itself does not fit in an i128 overflows. Returns None when no oracle
+    // is set, the supply is unknown, or the value would overflow.
+    pub fn get_asset_value(env: Env, asset_code: String) -> Option<i128> {
+        let asset_code = canonical_code(&env, &asset_code);
+        let asset = read_asset(&env, &asset_code)?;
+        let oracle: Address = env.storage().persistent().get(&AssetBook::PriceOracle(asset_code.clone()))?;
+        if !asset.supply_known {
+            return None;
+        }
+        
+        let price = PriceOracleClient::new(&env, &oracle).price(&asset_code);
+        I256::from_i128(&env, asset.total_supply)
+            .mul(&I256::from_i128(&env, price))
+            .div(&I256::from_i128(&env, 10i128.pow(asset.decimals)))
+            .to_i128()
+    }
+    
+    // Function to get the delegate of an asset, if any
+    pub fn get_delegate(env: Env, asset_code: String) -> Option<Address> {
+        let asset_code = canonical_code(&env, &asset_code);
+        env.storage().persistent().get(&AssetBook::Delegate(asset_code))
+    }
+    
+    // Function to delete every asset record, up to `limit` per call, working down from
+    // the last index slot (admin only). Registrations are rejected until a call
+    // reports the registry empty by returning true.
+    pub fn clear_registry(env: Env, limit: u32) -> bool {
+        require_admin(&env);
+        
+        let mut end = index_len(&env);
+        let mut removed = 0;
+        for _ in 0..MAX_SCAN {
+            if end == 0 || removed >= limit {
+                break;
+            }
+            
+            end -= 1;
+            match read_asset_at(&env, end) {
+                Some(asset) => {
+                    delete_asset(&env, &asset);
+                    removed += 1;
+                }
+                None => remove_entry(&env, &AssetBook::Index(end)),
+            }
+        }
+        
+        // Cleared slots are handed out again once the registry is empty
+        env.storage().instance().set(&NEXT_INDEX, &end);
+        let finished = end == 0;
+        if finished {
+            env.storage().instance().remove(&CLEARING);
+        } else {
+            env.storage().instance().set(&CLEARING, &true);
+        }
+        bump_ttl(&env);
+        
+        log!(&env, "Registry clear removed {} assets", removed);
+        finished
+    }
+    
+    // Function to hand control of an asset to a new issuer (current issuer only)
+    pub fn transfer_issuer(env: Env, asset_code: String, new_issuer: Address) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_not_paused(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        asset.issuer.require_auth();
+        
+        reassign_issuer(&env, &mut asset, &new_issuer, false);
+        
+        log!(&env, "Asset {} transferred to {}", asset_code, new_issuer);
+        true
+    }
+    
+    // Function to move an asset to a recovery issuer without the current issuer's
+    // cooperation (admin only)
+    pub fn admin_reassign_issuer(env: Env, asset_code: String, new_issuer: Address) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_admin(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        
+        reassign_issuer(&env, &mut asset, &new_issuer, true);
+        
+        log!(&env, "Admin reassigned {} to {}", asset_code, new_issuer);
+        true
+    }
+    
+    // Function to delist an asset (admin only); the record stays readable but no
+    // longer takes supply updates
+    pub fn deactivate_asset(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_admin(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        
+        if !asset.is_active {
+            log!(&env, "Asset already inactive: {}", asset_code);
+            panic_with_error!(&env, Error::AssetInactive);
+        }
+        
+        untrack_active(&env, &asset);
+        asset.is_active = false;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("inactive"), &asset_code, asset.issuer);
+        log!(&env, "Asset deactivated: {}", asset_code);
+        true
+    }
+    
+    // Function to restore a delisted, deprecated or retired asset (admin only).
+    // Reserved placeholders are activated by their issuer instead.
+    pub fn reactivate_asset(env: Env, asset_code: String) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_admin(&env);
+        let mut asset = load_asset(&env, &asset_code);
+        
+        if asset.is_active {
+            log!(&env, "Asset already active: {}", asset_code);
+            panic_with_error!(&env, Error::AssetActive);
+        }
+        if asset.reserved {
+            log!(&env, "Asset is only reserved: {}", asset_code);
+            panic_with_error!(&env, Error::AssetReserved);
+        }
+        
+        asset.is_active = true;
+        asset.successor = None;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        track_active(&env, &asset);
+        bump_ttl(&env);
+        
+        emit_event(&env, symbol_short!("active"), &asset_code, asset.issuer);
+        log!(&env, "Asset reactivated: {}", asset_code);
+        true
+    }
+    
+    // Function to mark an asset as reviewed by the admin
+    pub fn set_verified(env: Env, asset_code: String, verified: bool) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        require_admin(&env);
+        
+        let mut asset = load_asset(&env, &asset_code);
+        
+        asset.is_verified = verified;
+        asset.last_updated = env.ledger().timestamp();
+        
+        write_asset(&env, &asset);
+        bump_ttl(&env);
+        
+        log!(&env, "Asset verification set for: {}", asset_code);
+        true
+    }
+    
+    // Function to set the verified flag on many assets at once (admin only),
+    // skipping unknown codes; returns how many records changed
+    pub fn set_verified_batch(env: Env, codes: Vec<String>, verified: bool) -> u32 {
+        require_admin(&env);
+        
+        let mut changed = 0;
+        let time = env.ledger().timestamp();
+        
+        for asset_code in codes.iter() {
+            let mut asset = match read_asset(&env, &canonical_code(&env, &asset_code)) {
+                Some(asset) if asset.is_verified != verified => asset,
+                _ => continue,
+            };
+            
+            asset.is_verified = verified;
+            asset.last_updated = time;
+            write_asset(&env, &asset);
+            changed += 1;
+        }
+        
+        bump_ttl(&env);
+        
+        log!(&env, "Verification updated for {} assets", changed);
+        changed
+    }
+    
+    // Function to hide unverified assets from listings (admin only)
+    pub fn set_require_verified_for_listing(env: Env, enabled: bool) -> bool {
+        require_admin(&env);
+        
+        env.storage().instance().set(&REQUIRE_VERIFIED, &enabled);
+        bump_ttl(&env);
+        
+        log!(&env, "Verified-only listing set to: {}", enabled);
+        true
+    }
+    
+    // Function to get the supply that was in effect at a given time; fails with
+    // HistoryTruncated for times before checkpoints that were dropped
+    pub fn get_supply_at(env: Env, asset_code: String, timestamp: u64) -> Result<Option<i128>, Error> {
+        let asset_code = canonical_code(&env, &asset_code);
+        let history = supply_history(&env, &asset_code);
+        if history_truncated_before(&env, &asset_code, &history, timestamp) {
+            return Err(Error::HistoryTruncated);
+        }
+        
+        // Checkpoints are appended in time order, so the last one not after
+        // the requested time is the one in effect
+        let mut supply = None;
+        for checkpoint in history.iter() {
+            if checkpoint.timestamp > timestamp {
+                break;
+            }
+            supply = Some(checkpoint.supply);
+        }
+        
+        Ok(supply)
+    }
+    
+    // Function to get the time-weighted average supply over [from, to]. Time before the
+    // first checkpoint is weighted at the earliest known supply. Returns None without
+    // history, for a reversed window, or if the weighted sum overflows, and fails with
+    // HistoryTruncated when the window starts before checkpoints that were dropped.
+    pub fn get_twas(env: Env, asset_code: String, from: u64, to: u64) -> Result<Option<i128>, Error> {
+        let asset_code = canonical_code(&env, &asset_code);
+        let history = supply_history(&env, &asset_code);
+        if history_truncated_before(&env, &asset_code, &history, from) {
+            return Err(Error::HistoryTruncated);
+        }
+        
+        Ok(time_weighted_supply(&history, from, to))
+    }
+    
+    // Function to get an asset's retained supply checkpoints, oldest first; only the
+    // last MAX_HISTORY are kept
+    pub fn get_supply_history(env: Env, asset_code: String) -> Vec<SupplyCheckpoint> {
+        env.storage().persistent()
+            .get(&AssetBook::History(canonical_code(&env, &asset_code)))
+            .unwrap_or(Vec::new(&env))
+    }
+    
+    // Function to get a page of an asset's supply history as (timestamp, supply),
+    // oldest first
+    pub fn get_supply_history_page(env: Env, asset_code: String, offset: u32, limit: u32) -> Vec<(u64, i128)> {
+        let asset_code = canonical_code(&env, &asset_code);
+        let history: Vec<SupplyCheckpoint> = env.storage().persistent()
+            .get(&AssetBook::History(asset_code))
+            .unwrap_or(Vec::new(&env));
+        
+        let mut page = Vec::new(&env);
+        let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(history.len());
+        for index in offset..end {
+            let checkpoint = history.get_unchecked(index);
+            page.push_back((checkpoint.timestamp, checkpoint.supply));
+        }
+        
+        page
+    }
+    
+    // Function to get how long ago an asset was registered, in seconds
+    pub fn get_asset_age(env: Env, asset_code: String) -> Option<u64> {
+        let asset_code = canonical_code(&env, &asset_code);
+        read_asset(&env, &asset_code)
+            .map(|asset| env.ledger().timestamp().saturating_sub(asset.registration_time))
+    }
+    
+    // Function to get total number of registered assets
+    pub fn get_total_assets(env: Env) -> u64 {
+        env.storage().instance().get(&ASSET_COUNT).unwrap_or(0)
+    }
+    
+    // Function to get the sequence number of the last published event
+    pub fn get_event_seq(env: Env) -> u64 {
+        env.storage().instance().get(&EVENT_SEQ).unwrap_or(0)
+    }
+    
+    // Function to get up to `limit` of the most recent activity entries, newest first
+    pub fn get_recent_activity(env: Env, limit: u32) -> Vec<Activity> {
+        let activity: Vec<Activity> = env.storage().instance().get(&ACTIVITY).unwrap_or(Vec::new(&env));
+        
+        let mut recent = Vec::new(&env);
+        for entry in activity.iter().rev().take(limit.min(MAX_ACTIVITY) as usize) {
+            recent.push_back(entry);
+        }
+        
+        recent
+    }
+    
+    // Function to get number of registered assets that are active
+    pub fn get_active_asset_count(env: Env) -> u64 {
+        env.storage().instance().get(&ACTIVE_COUNT).unwrap_or(0)
+    }
+    
+    // Function to get the combined supply of all active assets, saturating at
+    // i128::MAX when the true total is larger
+    pub fn get_aggregate_supply(env: Env) -> i128 {
+        aggregate_supply(&env).to_i128().unwrap_or(i128::MAX)
+    }
+    
+    // Function to get an asset's share of the aggregate active supply in basis points
+    pub fn supply_share_bps(env: Env, asset_code: String) -> Option<u32> {
+        let asset_code = canonical_code(&env, &asset_code);
+        let asset = read_asset(&env, &asset_code)?;
+        let aggregate = aggregate_supply(&env);
+        if aggregate <= I256::from_i128(&env, 0) {
+            return None;
+        }
+        if !asset.supply_known {
+            return None;
+        }
+        if !asset.is_active {
+            return Some(0);
+        }
+        
+        // Multiply before dividing so small shares are not truncated to zero; at 256
+        // bits the product cannot overflow, even against an aggregate above i128::MAX
+        let scaled = I256::from_i128(&env, asset.total_supply).mul(&I256::from_i128(&env, 10_000));
+        let bps = scaled.div(&aggregate).to_i128().unwrap_or(10_000);
+        Some(bps.clamp(0, 10_000) as u32)
+    }
+    
+    // Function to estimate how many ledger entries the registry occupies: one for the
+    // contract instance, which holds every singleton, counter and config value, plus
+    // each persistent entry written and not yet removed (records, index slots,
+    // histories, live-until bounds, issuer lists, delegates, price oracles, allowlist
+    // and registrar entries, reputation and category counts). Temporary idempotency
+    // keys, which expire on their own, and first-release records still waiting in
+    // instance storage to be migrated are not included.
+    pub fn estimate_entry_count(env: Env) -> u64 {
+        let persistent: u64 = env.storage().instance().get(&ENTRY_COUNT).unwrap_or(0);
+        persistent.saturating_add(1)
+    }
+    
+    // Function to audit the registry's counters and indexes one scan window at a time;
+    // returns the name of the first broken invariant in the window starting at start,
+    // or None when it is consistent, along with the slot the next window starts at
+    pub fn check_invariants(env: Env, start: u64) -> (Option<Symbol>, u64) {
+        find_invariant_violation(&env, start)
+    }
+    
+    // Function for keepers to find assets whose entries may expire within the given
+    // number of ledgers. Expiry is judged by the lower bound recorded on each write,
+    // so an entry may live longer than reported; assets with no recorded bound are
+    // always returned. Up to MAX_SCAN index slots from start are searched; the
+    // returned slot is where the next call resumes.
+    pub fn assets_near_expiry(env: Env, within_ledgers: u32, start: u64, limit: u32) -> (Vec<String>, u64) {
+        let mut codes = Vec::new(&env);
+        let limit = scan_limit(limit);
+        let horizon = env.ledger().sequence().saturating_add(within_ledgers);
+        
+        let window = scan_window(&env, start);
+        let mut next = window.end;
+        for index in window {
+            if codes.len() >= limit {
+                next = index;
+                break;
+            }
+            
+            let code: Option<String> = env.storage().persistent().get(&AssetBook::Index(index));
+            if let Some(code) = code {
+                let live_until: Option<u32> = env.storage().persistent().get(&AssetBook::LiveUntil(code.clone()));
+                if live_until.is_none_or(|live_until| live_until < horizon) {
+                    codes.push_back(code);
+                }
+            }
+        }
+        
+        (codes, next)
+    }
+    
+    // Function for keepers to extend the storage lifetime of the given assets in one
+    // call, returning how many existing assets were refreshed
+    pub fn refresh_ttls(env: Env, codes: Vec<String>) -> u32 {
+        let mut refreshed = 0;
+        for code in codes.iter() {
+            // With the TTL policy disabled nothing is extended, so nothing counts
+            let extended = read_asset(&env, &canonical_code(&env, &code)).is_some_and(|asset| keep_asset_alive(&env, &asset));
+            if extended {
+                refreshed += 1;
+            }
+        }
+        
+        if refreshed > 0 {
+            bump_ttl(&env);
+        }
+        
+        log!(&env, "Refreshed TTL of {} assets", refreshed);
+        refreshed
+    }
+    
+    // Function for issuers and indexers to keep one asset's entries alive for at least
+    // extend_to more ledgers without touching the rest of the registry
+    pub fn extend_asset_ttl(env: Env, asset_code: String, extend_to: u32) -> bool {
+        let asset_code = canonical_code(&env, &asset_code);
+        if extend_to > env.storage().max_ttl() {
+            log!(&env, "Invalid TTL extension: {}", extend_to);
+            panic_with_error!(&env, Error::InvalidTtlConfig);
+        }
+        
+        let asset = load_asset(&env, &asset_code);
+        extend_asset_entries(&env, &asset, extend_to, extend_to);
+        note_asset_ttl(&env, &asset_code, extend_to, extend_to);
+        true
+    }
+    
+    // Function to get the index slot an asset occupies
+    pub fn get_asset_index(env: Env, asset_code: String) -> Option<u64> {
+        let asset_code = canonical_code(&env, &asset_code);
+        env.storage().persistent().get(&AssetBook::IndexOf(asset_code))
+    }
+    
+    // Function to get how many index slots have been handed out. Removed assets leave
+    // empty slots behind, so this is the bound to page list_assets up to rather than
+    // the asset count.
+    pub fn get_index_len(env: Env) -> u64 {
+        index_len(&env)
+    }
+    
+    // Function to get the asset stored at an index slot, or None for an empty slot
+    pub fn get_asset_at_index(env: Env, index: u64) -> Option<AssetInfo> {
+        read_asset_at(&env, index)
+    }
+    
+    // Function to get the code stored at an index slot, or None for an empty slot
+    pub fn get_asset_code_by_index(env: Env, index: u64) -> Option<String> {
+        env.storage().persistent().get(&AssetBook::Index(index))
+    }
+    
+    // Function to list active assets in the index window [start, start + limit)
+    pub fn list_assets(env: Env, start: u64, limit: u32) -> Vec<AssetInfo> {
+        let mut assets = Vec::new(&env);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(index_len(&env));
+        
+        for index in start..end {
+            if let Some(asset) = read_asset_at(&env, index) {
+                if is_listed(&env, &asset) {
+                    assets.push_back(asset);
+                }
+            }
+        }
+        
+        assets
+    }
+    
+    // Function to list just the codes of listed assets in the index window
+    // [start, start + limit), for building client-side search indexes
+    pub fn list_all_codes(env: Env, start: u64, limit: u32) -> Vec<String> {
+        let mut codes = Vec::new(&env);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(index_len(&env));
+        
+        for index in start..end {
+            if let Some(asset) = read_asset_at(&env, index) {
+                if is_listed(&env, &asset) {
+                    codes.push_back(asset.asset_code);
+                }
+            }
+        }
+        
+        codes
+    }
+    
+    // Function to list publicly listed assets with the given verification state in the
+    // index window [start, start + limit); while listing requires verification,
+    // unverified assets are hidden here too
+    pub fn list_assets_by_verification(env: Env, verified: bool, start: u64, limit: u32) -> Vec<AssetInfo> {
+        let mut assets = Vec::new(&env);
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(index_len(&env));
+        
+        for index in start..end {
+            if let Some(asset) = read_asset_at(&env, index) {
+                if is_listed(&env, &asset) && asset.is_verified == verified {
+                    assets.push_back(asset);
+                }
+            }
+        }
+        
+        assets
+    }
+    
+    // Function to export every stored record, inactive ones included, in the index
+    // window [offset, offset + limit) for backups (admin only)
+    pub fn export_all(env: Env, offset: u64, limit: u32) -> Vec<AssetInfo> {
+        require_admin(&env);
+        
+        let mut assets = Vec::new(&env);
+        let end = offset
+            .saturating_add(limit.min(MAX_PAGE_SIZE) as u64)
+            .min(index_len(&env));
+        
+        for index in offset..end {
+            if let Some(asset) = read_asset_at(&env, index) {
+                assets.push_back(asset);
+            }
+        }
+        
+        assets
+    }
+    
+    // Function to list active assets whose supply falls within [min, max]. Up to
+    // MAX_SCAN index slots from start are searched; the returned slot is where the
+    // next call resumes.
+    pub fn list_assets_by_supply_range(env: Env, min: i128, max: i128, start: u64, limit: u32) -> (Vec<AssetInfo>, u64) {
+        if min > max {
+            log!(&env, "Invalid supply range: {} > {}", min, max);
+            panic_with_error!(&env, Error::InvalidRange);
+        }
+        
+        let mut assets = Vec::new(&env);
+        let limit = scan_limit(limit);
+        
+        let window = scan_window(&env, start);
+        let mut next = window.end;
+        for index in window {
+            if assets.len() >= limit {
+                next = index;
+                break;
+            }
+            
+            if let Some(asset) = read_asset_at(&env, index) {
+                let in_range = asset.total_supply >= min && asset.total_supply <= max;
+                if is_listed(&env, &asset) && asset.supply_known && in_range {
+                    assets.push_back(asset);
+                }
+            }
+        }
+        
+        (assets, next)
+    }
+    
+    // Function to audit codes whose records were registered without issuer auth,
+    // searching up to MAX_SCAN index slots from start; the returned slot is where the
+    // next call resumes
+    pub fn list_unverified_issuer_assets(env: Env, start: u64, limit: u32) -> (Vec<String>, u64) {
+        let mut codes = Vec::new(&env);
+        let limit = scan_limit(limit);
+        
+        let window = scan_window(&env, start);
+        let mut next = window.end;
+        for index in window {
+            if codes.len() >= limit {
+                next = index;
+                break;
+            }
+            
+            if let Some(asset) = read_asset_at(&env, index) {
+                if !asset.issuer_verified {
+                    codes.push_back(asset.asset_code);
+                }
+            }
+        }
+        
+        (codes, next)
+    }
+    
+    // Function to pick a random listed asset for discovery widgets. Removed or hidden
+    // slots are re-rolled a bounded number of times, so a sparse registry may
+    // occasionally return None.
+    pub fn get_random_asset(env: Env) -> Option<AssetInfo> {
+        let slots = index_len(&env);
+        if slots == 0 || Self::get_active_asset_count(env.clone()) == 0 {
+            return None;
+        }
+        
+        for _ in 0..MAX_RANDOM_ATTEMPTS {
+            let index: u64 = env.prng().gen_range(0..slots);
+            if let Some(asset) = read_asset_at(&env, index) {
+                if is_listed(&env, &asset) {
+                    return Some(asset);
+                }
+            }
+        }
+        
+        None
+    }
+    
+    // Function to list assets changed at or after a timestamp, for incremental syncing.
+    // Up to MAX_SCAN index slots from start are searched for at most MAX_PAGE_SIZE
+    // assets; the returned slot is where the next call resumes.
+    pub fn get_assets_updated_since(env: Env, since: u64, start: u64, limit: u32) -> (Vec<AssetInfo>, u64) {
+        let mut assets = Vec::new(&env);
+        let limit = scan_limit(limit);
+        
+        let window = scan_window(&env, start);
+        let mut next = window.end;
+        for index in window {
+            if assets.len() >= limit {
+                next = index;
+                break;
+            }
+            
+            if let Some(asset) = read_asset_at(&env, index) {
+                if asset.last_updated >= since {
+                    assets.push_back(asset);
+                }
+            }
+        }
+        
+        (assets, next)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use soroban_sdk::testutils::{storage::Instance as _, storage::Persistent as _, storage::Temporary as _, Address as _, Events, Ledger, MockAuth, MockAuthInvoke};
+    use soroban_sdk::xdr::FromXdr;
+    use soroban_sdk::Env;
+    
+    // Oracle quoting whatever price the test last set
+    #[contract]
+    pub struct MockOracle;
+    
+    #[contractimpl]
+    impl MockOracle {
+        pub fn set_price(env: Env, price: i128) {
+            env.storage().instance().set(&symbol_short!("PRICE"), &price);
+        }
+        
+        pub fn price(env: Env, _asset_code: String) -> i128 {
+            env.storage().instance().get(&symbol_short!("PRICE")).unwrap_or(0)
+        }
+    }
+    
+    fn default_config() -> RegistryConfig {
+        RegistryConfig {
+            ttl: TtlConfig { enabled: true, threshold: 5000, extend_to: 5000 },
+            registration_mode: RegistrationMode::Open,
+        }
+    }
+    
+    fn setup_with_admin<'a>(env: &Env, admin: &Address) -> AssetExplorerContractClient<'a> {
+        let contract_id = env.register(AssetExplorerContract, (admin.clone(), default_config()));
+        AssetExplorerContractClient::new(env, &contract_id)
+    }
+    
+    fn setup(env: &Env) -> AssetExplorerContractClient<'_> {
+        setup_with_admin(env, &Address::generate(env))
+    }
+
+    #[test]
+    fn test_register_and_get_asset() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        
+        let asset_code = String::from_str(&env, "USDC");
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "USD Coin");
+        
+        // Register asset
+        client.register_asset(&asset_code, &issuer, &1000000, &description);
+        
+        // Get asset info
+        let asset_info = client.get_asset_info(&asset_code).unwrap();
+        assert_eq!(asset_info.asset_code, asset_code);
+        assert_eq!(asset_info.total_supply, 1000000);
+    }
+    
+    #[test]
+    fn test_failures_return_contract_errors() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "USD Coin");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        assert!(client.get_asset_info(&usdc).is_none());
+        assert_eq!(client.try_update_asset_supply(&issuer, &usdc, &1000), Err(Ok(Error::AssetNotFound)));
+        
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        assert_eq!(client.try_register_asset(&usdc, &issuer, &1000, &description), Err(Ok(Error::AssetAlreadyExists)));
+        assert_eq!(client.try_register_asset(&eurc, &issuer, &-1, &description), Err(Ok(Error::InvalidSupply)));
+        assert_eq!(client.try_update_asset_supply(&issuer, &usdc, &-1), Err(Ok(Error::InvalidSupply)));
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 1000);
+    }
+    
+    #[test]
+    fn test_get_assets_updated_since() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        
+        env.ledger().set_timestamp(100);
+        client.register_asset(&String::from_str(&env, "USDC"), &issuer, &1000, &description);
+        client.register_asset(&String::from_str(&env, "EURC"), &issuer, &2000, &description);
+        client.register_asset(&String::from_str(&env, "BTC"), &issuer, &3000, &description);
+        
+        // Only EURC changes after the initial registrations
+        env.ledger().set_timestamp(200);
+        client.update_asset_supply(&issuer, &String::from_str(&env, "EURC"), &2500);
+        
+        let updated = client.get_assets_updated_since(&150, &0, &10).0;
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated.get(0).unwrap().asset_code, String::from_str(&env, "EURC"));
+        assert_eq!(updated.get(0).unwrap().last_updated, 200);
+        
+        // An early cursor returns everything, bounded by the limit
+        assert_eq!(client.get_assets_updated_since(&0, &0, &10).0.len(), 3);
+        assert_eq!(client.get_assets_updated_since(&0, &0, &2).0.len(), 2);
+    }
+    
+    #[test]
+    fn test_register_asset_minimal_uses_default_description() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        
+        // Without configuration the built-in default is used
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset_minimal(&usdc, &issuer, &1000);
+        assert_eq!(
+            client.get_asset_info(&usdc).unwrap().description,
+            String::from_str(&env, "No description provided")
+        );
+        
+        // The admin can change the template for later registrations
+        let custom = String::from_str(&env, "Pending issuer review");
+        client.set_default_description(&custom);
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset_minimal(&eurc, &issuer, &2000);
+        assert_eq!(client.get_asset_info(&eurc).unwrap().description, custom);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Error(Contract, #9)")]
+    fn test_initialize_after_construction_fails() {
+        let env = Env::default();
+        let client = setup(&env);
+        
+        client.initialize(&Address::generate(&env));
+    }
+    
+    #[test]
+    fn test_get_supply_at() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let asset_code = String::from_str(&env, "USDC");
+        
+        env.ledger().set_timestamp(100);
+        client.register_asset(&asset_code, &issuer, &1000, &String::from_str(&env, "USD Coin"));
+        env.ledger().set_timestamp(200);
+        client.update_asset_supply(&issuer, &asset_code, &1500);
+        env.ledger().set_timestamp(300);
+        client.update_asset_supply(&issuer, &asset_code, &1200);
+        
+        // Before registration the asset did not exist
+        assert_eq!(client.get_supply_at(&asset_code, &99), None);
+        
+        // Lookups on and between checkpoints resolve to the supply in effect
+        assert_eq!(client.get_supply_at(&asset_code, &100), Some(1000));
+        assert_eq!(client.get_supply_at(&asset_code, &150), Some(1000));
+        assert_eq!(client.get_supply_at(&asset_code, &200), Some(1500));
+        assert_eq!(client.get_supply_at(&asset_code, &299), Some(1500));
+        assert_eq!(client.get_supply_at(&asset_code, &1000), Some(1200));
+        
+        // Unknown assets have no history
+        assert_eq!(client.get_supply_at(&String::from_str(&env, "EURC"), &1000), None);
+    }
+    
+    #[test]
+    fn test_registry_info() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        
+        let empty = String::from_str(&env, "");
+        assert_eq!(client.get_registry_info(), (empty.clone(), empty));
+        
+        let name = String::from_str(&env, "Stellar Asset Explorer");
+        let description = String::from_str(&env, "Community registry of Stellar assets");
+        client.set_registry_info(&name, &description);
+        assert_eq!(client.get_registry_info(), (name, description));
+    }
+    
+    #[test]
+    fn test_require_verified_for_listing() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &2000, &description);
+        client.set_verified(&usdc, &true);
+        
+        // Listings include everything active until the toggle is on
+        assert_eq!(client.list_assets(&0, &10).len(), 2);
+        
+        client.set_require_verified_for_listing(&true);
+        let listed = client.list_assets(&0, &10);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed.get(0).unwrap().asset_code, usdc);
+        
+        // Direct lookups still return the unverified asset
+        assert_eq!(client.get_asset_info(&eurc).unwrap().asset_code, eurc);
+        
+        // Once verified it shows up again
+        client.set_verified(&eurc, &true);
+        assert_eq!(client.list_assets(&0, &10).len(), 2);
+    }
+    
+    #[test]
+    fn test_get_asset_age() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let asset_code = String::from_str(&env, "USDC");
+        
+        env.ledger().set_timestamp(1_000);
+        client.register_asset(&asset_code, &Address::generate(&env), &1000, &String::from_str(&env, "USD Coin"));
+        assert_eq!(client.get_asset_age(&asset_code), Some(0));
+        
+        env.ledger().set_timestamp(4_600);
+        assert_eq!(client.get_asset_age(&asset_code), Some(3_600));
+        
+        // A clock behind the registration time yields zero rather than underflowing
+        env.ledger().set_timestamp(500);
+        assert_eq!(client.get_asset_age(&asset_code), Some(0));
+        
+        assert_eq!(client.get_asset_age(&String::from_str(&env, "EURC")), None);
+    }
+    
+    #[test]
+    fn test_delegate_can_update_supply() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let delegate = Address::generate(&env);
+        let stranger = Address::generate(&env);
+        let asset_code = String::from_str(&env, "USDC");
+        client.register_asset(&asset_code, &issuer, &1000, &String::from_str(&env, "USD Coin"));
+        
+        client.set_delegate(&asset_code, &delegate);
+        assert_eq!(client.get_delegate(&asset_code), Some(delegate.clone()));
+        
+        client.update_asset_supply(&delegate, &asset_code, &2000);
+        assert_eq!(client.get_asset_info(&asset_code).unwrap().total_supply, 2000);
+        
+        // Addresses other than the issuer or delegate are rejected
+        assert!(client.try_update_asset_supply(&stranger, &asset_code, &3000).is_err());
+        
+        // Once removed, the former delegate loses access but the issuer keeps it
+        client.remove_delegate(&asset_code);
+        assert_eq!(client.get_delegate(&asset_code), None);
+        assert!(client.try_update_asset_supply(&delegate, &asset_code, &3000).is_err());
+        client.update_asset_supply(&issuer, &asset_code, &3000);
+        assert_eq!(client.get_asset_info(&asset_code).unwrap().total_supply, 3000);
+    }
+    
+    #[test]
+    fn test_remove_asset_updates_counters_and_index() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &2000, &description);
+        assert_eq!(client.get_active_asset_count(), 2);
+        
+        client.remove_asset(&usdc);
+        assert_eq!(client.get_total_assets(), 1);
+        assert_eq!(client.get_active_asset_count(), 1);
+        assert_eq!(client.get_asset_index(&usdc), None);
+        assert!(client.get_asset_info(&usdc).is_none());
+        
+        let listed = client.list_assets(&0, &10);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed.get(0).unwrap().asset_code, eurc);
+        
+        // The removed slot stays behind, so paging up to the index length rather
+        // than the asset count still reaches the later asset
+        assert_eq!(client.get_index_len(), 2);
+        assert_eq!(client.list_assets(&0, &1).len(), 0);
+        let mut paged = Vec::new(&env);
+        let mut start = 0;
+        while start < client.get_index_len() {
+            paged.append(&client.list_assets(&start, &1));
+            start += 1;
+        }
+        assert_eq!(paged.len(), 1);
+        assert_eq!(paged.get(0).unwrap().asset_code, eurc);
+    }
+    
+    #[test]
+    fn test_recompute_active_count_after_desync() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let btc = String::from_str(&env, "BTC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &2000, &description);
+        client.register_asset(&btc, &issuer, &3000, &description);
+        
+        client.set_category(&eurc, &symbol_short!("stable"));
+        client.set_category(&btc, &symbol_short!("crypto"));
+        
+        // Corrupt the counters so removals would underflow them
+        let category_count = |category: Symbol| {
+            env.as_contract(&client.address, || {
+                env.storage().persistent().get::<_, u32>(&AssetBook::CategoryCount(category))
+            })
+        };
+        env.as_contract(&client.address, || {
+            env.storage().instance().set(&ACTIVE_COUNT, &1u64);
+            env.storage().instance().set(&AGG_SUPPLY, &I256::from_i128(&env, 500));
+            env.storage().instance().set(&CATEGORIES, &Vec::from_array(&env, [symbol_short!("stable")]));
+            env.storage().persistent().set(&AssetBook::CategoryCount(symbol_short!("stable")), &5u32);
+            env.storage().persistent().remove(&AssetBook::CategoryCount(symbol_short!("crypto")));
+        });
+        client.remove_asset(&usdc);
+        client.remove_asset(&eurc);
+        assert_eq!(client.get_active_asset_count(), 0);
+        
+        // Removed and repeated codes do not inflate the recomputed counters
+        let codes = Vec::from_array(&env, [usdc, eurc, btc.clone(), btc]);
+        assert_eq!(client.recompute_active_count(&codes), 1);
+        assert_eq!(client.get_active_asset_count(), 1);
+        assert_eq!(client.get_aggregate_supply(), 3000);
+        assert_eq!(client.get_categories(), Vec::from_array(&env, [symbol_short!("crypto")]));
+        assert_eq!(category_count(symbol_short!("crypto")), Some(1));
+        assert_eq!(category_count(symbol_short!("stable")), None);
+        assert_eq!(client.check_invariants(&0).0, None);
+    }
+    
+    #[test]
+    fn test_list_assets_pages_in_registration_order() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let codes = ["AST0", "AST1", "AST2", "AST3", "AST4", "AST5", "AST6", "AST7", "AST8", "AST9"]
+            .map(|code| String::from_str(&env, code));
+        for code in codes.iter() {
+            client.register_asset(code, &issuer, &1000, &description);
+        }
+        
+        // Walking pages of 3 reaches every asset once, ending on a partial page
+        let mut start = 0;
+        let mut seen = 0;
+        while start < 12 {
+            let page = client.list_assets(&start, &3);
+            assert_eq!(page.len(), if start < 9 { 3 } else if start == 9 { 1 } else { 0 });
+            for asset in page.iter() {
+                assert_eq!(asset.asset_code, codes[seen]);
+                assert_eq!(client.get_asset_code_by_index(&(seen as u64)), Some(codes[seen].clone()));
+                seen += 1;
+            }
+            start += 3;
+        }
+        assert_eq!(seen, codes.len());
+        assert_eq!(client.get_asset_code_by_index(&10), None);
+        assert_eq!(client.list_assets(&0, &1000).len(), 10);
+    }
+    
+    #[test]
+    fn test_list_assets_by_supply_range() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        client.register_asset(&String::from_str(&env, "SMALL"), &issuer, &500_000, &description);
+        client.register_asset(&String::from_str(&env, "LOW"), &issuer, &1_000_000, &description);
+        client.register_asset(&String::from_str(&env, "MID"), &issuer, &50_000_000, &description);
+        client.register_asset(&String::from_str(&env, "HIGH"), &issuer, &100_000_000, &description);
+        client.register_asset(&String::from_str(&env, "HUGE"), &issuer, &500_000_000, &description);
+        
+        // Both bounds are inclusive
+        let in_range = client.list_assets_by_supply_range(&1_000_000, &100_000_000, &0, &10).0;
+        assert_eq!(in_range.len(), 3);
+        assert_eq!(in_range.get(0).unwrap().asset_code, String::from_str(&env, "LOW"));
+        assert_eq!(in_range.get(1).unwrap().asset_code, String::from_str(&env, "MID"));
+        assert_eq!(in_range.get(2).unwrap().asset_code, String::from_str(&env, "HIGH"));
+        
+        assert_eq!(client.list_assets_by_supply_range(&1_000_000, &100_000_000, &0, &2).0.len(), 2);
+        assert!(client.try_list_assets_by_supply_range(&10, &1, &0, &10).is_err());
+    }
+    
+    #[test]
+    fn test_register_asset_checked_results() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        
+        assert_eq!(client.register_asset_checked(&usdc, &issuer, &1000, &description), RegisterResult::Registered);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 1000);
+        
+        // A duplicate leaves the original record untouched
+        assert_eq!(client.register_asset_checked(&usdc, &issuer, &5000, &description), RegisterResult::AlreadyExists);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 1000);
+        
+        for code in ["", "TOOLONGASSETCODE", "US DC"] {
+            let code = String::from_str(&env, code);
+            assert_eq!(client.register_asset_checked(&code, &issuer, &1000, &description), RegisterResult::InvalidCode);
+        }
+        
+        // In allowlist mode only approved issuers may register
+        client.set_registration_mode(&RegistrationMode::Allowlist);
+        let eurc = String::from_str(&env, "EURC");
+        assert_eq!(client.register_asset_checked(&eurc, &issuer, &1000, &description), RegisterResult::Unauthorized);
+        client.set_issuer_allowed(&issuer, &true);
+        assert_eq!(client.register_asset_checked(&eurc, &issuer, &1000, &description), RegisterResult::Registered);
+        
+        assert_eq!(client.get_total_assets(), 2);
+    }
+    
+    #[test]
+    #[should_panic(expected = "Error(Contract, #4)")]
+    fn test_register_invalid_code_fails() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        
+        client.register_asset(
+            &String::from_str(&env, "NOT-A-CODE"),
+            &Address::generate(&env),
+            &1000,
+            &String::from_str(&env, "Test asset"),
+        );
+    }
+    
+    #[test]
+    fn test_adjust_reputation() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        assert_eq!(client.get_reputation(&issuer), 0);
+        
+        assert_eq!(client.adjust_reputation(&issuer, &25), 25);
+        assert_eq!(client.adjust_reputation(&issuer, &-10), 15);
+        assert_eq!(client.get_reputation(&issuer), 15);
+        
+        // Scores are clamped at both ends
+        assert_eq!(client.adjust_reputation(&issuer, &i32::MAX), MAX_REPUTATION);
+        assert_eq!(client.adjust_reputation(&issuer, &1), MAX_REPUTATION);
+        assert_eq!(client.adjust_reputation(&issuer, &i32::MIN), MIN_REPUTATION);
+        assert_eq!(client.get_reputation(&issuer), MIN_REPUTATION);
+    }
+    
+    #[test]
+    fn test_estimate_entry_count() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        
+        // Everything the constructor sets lives in the single instance entry
+        assert_eq!(client.estimate_entry_count(), 1);
+        
+        // A first registration writes the record, both index entries, the history, the
+        // live-until bound and the issuer's list; the issuer's second asset reuses the list
+        client.register_asset(&String::from_str(&env, "USDC"), &issuer, &1000, &description);
+        assert_eq!(client.estimate_entry_count(), 7);
+        client.register_asset(&String::from_str(&env, "EURC"), &issuer, &1000, &description);
+        assert_eq!(client.estimate_entry_count(), 12);
+        
+        // Optional per-asset entries are counted while they exist
+        client.set_delegate(&String::from_str(&env, "EURC"), &Address::generate(&env));
+        assert_eq!(client.estimate_entry_count(), 13);
+        
+        // Removal takes every entry of the asset with it
+        client.remove_asset(&String::from_str(&env, "USDC"));
+        assert_eq!(client.estimate_entry_count(), 8);
+        
+        // Assets without a known supply have no history, and with the TTL policy off
+        // no live-until bound is recorded
+        client.set_ttl_config(&TtlConfig { enabled: false, threshold: 0, extend_to: 0 });
+        client.register_asset_pending_supply(&String::from_str(&env, "PEND"), &issuer, &description);
+        assert_eq!(client.estimate_entry_count(), 11);
+    }
+    
+    #[test]
+    fn test_lock_description() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let asset_code = String::from_str(&env, "USDC");
+        client.register_asset(&asset_code, &Address::generate(&env), &1000, &String::from_str(&env, "USD Coin"));
+        
+        let revised = String::from_str(&env, "USD Coin, fully reserved");
+        client.update_description(&asset_code, &revised);
+        assert_eq!(client.get_asset_info(&asset_code).unwrap().description, revised);
+        
+        client.lock_description(&asset_code);
+        assert!(client.get_asset_info(&asset_code).unwrap().description_locked);
+        assert!(client.try_update_description(&asset_code, &String::from_str(&env, "Changed")).is_err());
+        assert_eq!(client.get_asset_info(&asset_code).unwrap().description, revised);
+    }
+    
+    #[test]
+    fn test_get_asset_info_ci() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        client.register_asset(&String::from_str(&env, "EURC"), &issuer, &500, &description);
+        client.register_asset(&String::from_str(&env, "USDC"), &issuer, &1000, &description);
+        
+        let exact = client.get_asset_info_ci(&String::from_str(&env, "USDC"), &0).0.unwrap();
+        assert_eq!(exact.total_supply, 1000);
+        
+        let folded = client.get_asset_info_ci(&String::from_str(&env, "usdc"), &0).0.unwrap();
+        assert_eq!(folded.asset_code, String::from_str(&env, "USDC"));
+        assert_eq!(client.get_asset_info_ci(&String::from_str(&env, "uSdC"), &0).0.unwrap().total_supply, 1000);
+        
+        assert!(client.get_asset_info_ci(&String::from_str(&env, "usd"), &0).0.is_none());
+        assert!(client.get_asset_info_ci(&String::from_str(&env, "btc"), &0).0.is_none());
+    }
+    
+    #[test]
+    fn test_set_metadata_uris_batch() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let other = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let btc = String::from_str(&env, "BTC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.register_asset(&btc, &other, &1000, &description);
+        
+        let uri = String::from_str(&env, "ipfs://new-gateway/metadata.json");
+        let updates = Vec::from_array(&env, [
+            (usdc.clone(), uri.clone()),
+            (eurc.clone(), uri.clone()),
+            (btc.clone(), uri.clone()),
+            (String::from_str(&env, "NOPE"), uri.clone()),
+        ]);
+        assert_eq!(client.set_metadata_uris_batch(&issuer, &updates), 2);
+        
+        assert_eq!(client.get_asset_info(&usdc).unwrap().metadata_uri, Some(uri.clone()));
+        assert_eq!(client.get_asset_info(&eurc).unwrap().metadata_uri, Some(uri));
+        assert_eq!(client.get_asset_info(&btc).unwrap().metadata_uri, None);
+        
+        // An invalid URI rejects the whole batch
+        let bad = Vec::from_array(&env, [(usdc, String::from_str(&env, ""))]);
+        assert!(client.try_set_metadata_uris_batch(&issuer, &bad).is_err());
+    }
+    
+    #[test]
+    fn test_deprecate_asset_with_successor() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let token = String::from_str(&env, "TOKEN");
+        let token_v2 = String::from_str(&env, "TOKENV2");
+        client.register_asset(&token, &issuer, &1000, &description);
+        client.register_asset(&token_v2, &issuer, &1000, &description);
+        assert_eq!(client.get_successor(&token), None);
+        
+        // The successor is validated like a registered code and stored canonically
+        for bad in ["", "TOKEN-V2", "TOKENV2TOKENV2"] {
+            let bad = String::from_str(&env, bad);
+            assert!(client.try_deprecate_asset(&token, &bad).is_err());
+        }
+        client.deprecate_asset(&token, &String::from_str(&env, " TOKENV2 "));
+        assert_eq!(client.get_successor(&token), Some(token_v2.clone()));
+        assert!(!client.get_asset_info(&token).unwrap().is_active);
+        assert_eq!(client.get_active_asset_count(), 1);
+        
+        // A deprecated asset drops out of listings and rejects supply changes
+        let listed = client.list_assets(&0, &10);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed.get(0).unwrap().asset_code, token_v2);
+        assert!(client.try_update_asset_supply(&issuer, &token, &5).is_err());
+        assert!(client.try_deprecate_asset(&token, &token_v2).is_err());
+    }
+    
+    #[test]
+    fn test_health_reports_init_and_pause() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        assert_eq!(client.health(), (true, false));
+        
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        assert_eq!(client.health(), (true, false));
+        
+        // While paused, registrations and asset changes are rejected
+        client.pause();
+        assert_eq!(client.health(), (true, true));
+        let eurc = String::from_str(&env, "EURC");
+        assert_eq!(client.register_asset_checked(&eurc, &issuer, &1000, &description), RegisterResult::Paused);
+        assert!(client.try_update_asset_supply(&issuer, &usdc, &2000).is_err());
+        
+        client.unpause();
+        assert_eq!(client.health(), (true, false));
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 2000);
+    }
+    
+    #[test]
+    fn test_ttl_config_applies_on_writes() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let instance_ttl = || env.as_contract(&client.address, || env.storage().instance().get_ttl());
+        
+        client.set_ttl_config(&TtlConfig { enabled: true, threshold: 9_000, extend_to: 10_000 });
+        assert_eq!(client.get_ttl_config().extend_to, 10_000);
+        client.register_asset(&String::from_str(&env, "USDC"), &issuer, &1000, &description);
+        assert_eq!(instance_ttl(), 10_000);
+        
+        // With extension disabled, writes leave the TTL alone as ledgers pass
+        client.set_ttl_config(&TtlConfig { enabled: false, threshold: 0, extend_to: 0 });
+        env.ledger().with_mut(|ledger| ledger.sequence_number += 100);
+        client.register_asset(&String::from_str(&env, "EURC"), &issuer, &1000, &description);
+        assert_eq!(instance_ttl(), 9_900);
+        
+        let invalid = TtlConfig { enabled: true, threshold: 10, extend_to: 5 };
+        assert!(client.try_set_ttl_config(&invalid).is_err());
+    }
+    
+    #[test]
+    fn test_list_unverified_issuer_assets() {
+        let env = Env::default();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let registrar = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let vouched = String::from_str(&env, "VOUCHED");
+        let signed = String::from_str(&env, "SIGNED");
+        
+        // Without the issuer's signature registration is refused
+        assert!(client.try_register_asset(&signed, &issuer, &1000, &description).is_err());
+        
+        // A registrar registers on the issuer's behalf without its signature
+        env.mock_all_auths();
+        client.add_registrar(&registrar);
+        client.register_verified_asset(&registrar, &vouched, &issuer, &1000, &description);
+        client.register_asset(&signed, &issuer, &1000, &description);
+        assert!(client.get_asset_info(&signed).unwrap().issuer_verified);
+        assert!(!client.get_asset_info(&vouched).unwrap().issuer_verified);
+        
+        let audit = client.list_unverified_issuer_assets(&0, &10).0;
+        assert_eq!(audit, Vec::from_array(&env, [vouched]));
+    }
+    
+    #[test]
+    fn test_supply_share_bps() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let dust = String::from_str(&env, "DUST");
+        assert_eq!(client.supply_share_bps(&usdc), None);
+        
+        client.register_asset(&usdc, &issuer, &7_500, &description);
+        client.register_asset(&eurc, &issuer, &2_499, &description);
+        client.register_asset(&dust, &issuer, &1, &description);
+        assert_eq!(client.get_aggregate_supply(), 10_000);
+        assert_eq!(client.supply_share_bps(&usdc), Some(7_500));
+        assert_eq!(client.supply_share_bps(&eurc), Some(2_499));
+        assert_eq!(client.supply_share_bps(&dust), Some(1));
+        assert_eq!(client.supply_share_bps(&String::from_str(&env, "BTC")), None);
+        
+        // Supply changes and removals keep the aggregate in step
+        client.update_asset_supply(&issuer, &eurc, &12_499);
+        client.remove_asset(&dust);
+        assert_eq!(client.get_aggregate_supply(), 19_999);
+        assert_eq!(client.supply_share_bps(&usdc), Some(3_750));
+    }
+    
+    #[test]
+    fn test_huge_supply_does_not_block_other_issuers() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let whale = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let huge = String::from_str(&env, "HUGE");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset(&eurc, &issuer, &10, &description);
+        client.register_asset(&huge, &whale, &i128::MAX, &description);
+        
+        // Everyone else can still register and update past the i128 total
+        client.register_asset(&usdc, &issuer, &1, &description);
+        client.update_asset_supply(&issuer, &eurc, &5);
+        assert_eq!(client.get_aggregate_supply(), i128::MAX);
+        assert_eq!(client.supply_share_bps(&huge), Some(9_999));
+        assert_eq!(client.supply_share_bps(&usdc), Some(0));
+        assert_eq!(client.check_invariants(&0).0, None);
+        
+        // Removing the huge asset brings the exact total back
+        client.remove_asset(&huge);
+        assert_eq!(client.get_aggregate_supply(), 6);
+        assert_eq!(client.supply_share_bps(&usdc), Some(1_666));
+    }
+    
+    #[test]
+    fn test_can_register_matches_register() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let bad_code = String::from_str(&env, "NOT_FOUND");
+        
+        assert_eq!(client.can_register(&usdc, &issuer, &1000), RegisterResult::Registered);
+        assert_eq!(client.get_total_assets(), 0);
+        assert_eq!(client.register_asset_checked(&usdc, &issuer, &1000, &description), RegisterResult::Registered);
+        
+        let cases = [
+            (usdc.clone(), 1000, RegisterResult::AlreadyExists),
+            (bad_code, 1000, RegisterResult::InvalidCode),
+            (String::from_str(&env, "EURC"), -1, RegisterResult::InvalidSupply),
+        ];
+        for (code, supply, expected) in cases {
+            assert_eq!(client.can_register(&code, &issuer, &supply), expected);
+            assert_eq!(client.register_asset_checked(&code, &issuer, &supply, &description), expected);
+            assert!(client.try_register_asset(&code, &issuer, &supply, &description).is_err());
+        }
+        
+        client.set_registration_mode(&RegistrationMode::Allowlist);
+        let btc = String::from_str(&env, "BTC");
+        assert_eq!(client.can_register(&btc, &issuer, &1000), RegisterResult::Unauthorized);
+        assert_eq!(client.register_asset_checked(&btc, &issuer, &1000, &description), RegisterResult::Unauthorized);
+        assert_eq!(client.get_total_assets(), 1);
+    }
+    
+    #[test]
+    fn test_get_categories() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let btc = String::from_str(&env, "BTC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.register_asset(&btc, &issuer, &1000, &description);
+        assert_eq!(client.get_categories().len(), 0);
+        
+        let stable = symbol_short!("stable");
+        let crypto = symbol_short!("crypto");
+        client.set_category(&usdc, &stable);
+        client.set_category(&eurc, &stable);
+        client.set_category(&btc, &crypto);
+        assert_eq!(client.get_categories(), Vec::from_array(&env, [stable.clone(), crypto.clone()]));
+        
+        // A category stays listed until its last active asset leaves
+        client.remove_asset(&usdc);
+        assert_eq!(client.get_categories(), Vec::from_array(&env, [stable.clone(), crypto.clone()]));
+        client.deprecate_asset(&eurc, &usdc);
+        assert_eq!(client.get_categories(), Vec::from_array(&env, [crypto.clone()]));
+        
+        // Moving an asset between categories prunes the one it left
+        client.set_category(&btc, &stable);
+        assert_eq!(client.get_categories(), Vec::from_array(&env, [stable]));
+    }
+    
+    #[test]
+    fn test_pending_supply_excluded_until_known() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let newco = String::from_str(&env, "NEWCO");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset_pending_supply(&newco, &issuer, &description);
+        
+        assert!(!client.get_asset_info(&newco).unwrap().supply_known);
+        assert_eq!(client.get_aggregate_supply(), 1000);
+        assert_eq!(client.supply_share_bps(&newco), None);
+        assert_eq!(client.supply_share_bps(&usdc), Some(10_000));
+        assert_eq!(client.list_assets_by_supply_range(&0, &10_000, &0, &10).0.len(), 1);
+        assert_eq!(client.get_supply_at(&newco, &u64::MAX), None);
+        
+        client.update_asset_supply(&issuer, &newco, &3000);
+        assert!(client.get_asset_info(&newco).unwrap().supply_known);
+        assert_eq!(client.get_aggregate_supply(), 4000);
+        assert_eq!(client.supply_share_bps(&newco), Some(7_500));
+        assert_eq!(client.list_assets_by_supply_range(&0, &10_000, &0, &10).0.len(), 2);
+    }
+    
+    #[test]
+    fn test_set_verified_batch() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        
+        let codes = Vec::from_array(&env, [usdc.clone(), String::from_str(&env, "NOPE"), eurc.clone()]);
+        assert_eq!(client.set_verified_batch(&codes, &true), 2);
+        assert!(client.get_asset_info(&usdc).unwrap().is_verified);
+        assert!(client.get_asset_info(&eurc).unwrap().is_verified);
+        
+        // Records already in the requested state are not counted again
+        assert_eq!(client.set_verified_batch(&codes, &true), 0);
+        assert_eq!(client.set_verified_batch(&Vec::from_array(&env, [usdc.clone()]), &false), 1);
+        assert!(!client.get_asset_info(&usdc).unwrap().is_verified);
+    }
+    
+    #[test]
+    fn test_get_asset_raw_round_trips() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let asset_code = String::from_str(&env, "USDC");
+        client.register_asset(&asset_code, &Address::generate(&env), &1000, &String::from_str(&env, "USD Coin"));
+        
+        let raw = client.get_asset_raw(&asset_code).unwrap();
+        let decoded = AssetInfo::from_xdr(&env, &raw).unwrap();
+        assert_eq!(decoded, client.get_asset_info(&asset_code).unwrap());
+        
+        assert_eq!(client.get_asset_raw(&String::from_str(&env, "EURC")), None);
+    }
+    
+    #[test]
+    fn test_reregister_after_removal_gets_fresh_index() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        env.ledger().set_timestamp(100);
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.set_delegate(&usdc, &Address::generate(&env));
+        assert_eq!(client.get_asset_index(&usdc), Some(0));
+        
+        client.remove_asset(&usdc);
+        env.ledger().set_timestamp(500);
+        client.register_asset(&usdc, &issuer, &2000, &description);
+        
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert_eq!(client.get_asset_index(&usdc), Some(2));
+        assert_eq!(asset.registration_time, 500);
+        assert_eq!(asset.total_supply, 2000);
+        assert_eq!(client.get_delegate(&usdc), None);
+        assert_eq!(client.get_supply_at(&usdc, &100), None);
+        assert_eq!(client.get_total_assets(), 2);
+        assert_eq!(client.get_active_asset_count(), 2);
+        
+        // The old slot stays empty and the code is listed exactly once
+        let listed = client.list_assets(&0, &10);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed.get(0).unwrap().asset_code, eurc);
+        assert_eq!(listed.get(1).unwrap().asset_code, usdc);
+    }
+    
+    #[test]
+    fn test_whole_units_only_policy() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let unit = 10_000_000; // one whole unit at the default 7 decimals
+        let usdc = String::from_str(&env, "USDC");
+        
+        // With the policy off any supply is accepted
+        client.register_asset(&usdc, &issuer, &(unit + 1), &description);
+        client.update_asset_supply(&issuer, &usdc, &5);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().decimals, DEFAULT_DECIMALS);
+        
+        client.set_whole_units_only(&true);
+        let eurc = String::from_str(&env, "EURC");
+        assert_eq!(client.can_register(&eurc, &issuer, &(unit / 2)), RegisterResult::FractionalSupply);
+        assert_eq!(client.register_asset_checked(&eurc, &issuer, &(unit / 2), &description), RegisterResult::FractionalSupply);
+        assert_eq!(client.try_register_asset(&eurc, &issuer, &(unit / 2), &description), Err(Ok(Error::FractionalSupply)));
+        assert_eq!(client.try_register_asset(&eurc, &issuer, &-unit, &description), Err(Ok(Error::InvalidSupply)));
+        client.register_asset(&eurc, &issuer, &(3 * unit), &description);
+        
+        assert_eq!(client.try_update_asset_supply(&issuer, &eurc, &(unit + 1)), Err(Ok(Error::FractionalSupply)));
+        client.update_asset_supply(&issuer, &eurc, &(4 * unit));
+        
+        // Decimals set per asset change what counts as a whole unit
+        client.set_decimals(&eurc, &2);
+        client.update_asset_supply(&issuer, &eurc, &300);
+        assert!(client.try_update_asset_supply(&issuer, &eurc, &350).is_err());
+        assert!(client.try_set_decimals(&eurc, &3).is_err());
+    }
+    
+    #[test]
+    fn test_get_random_asset() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        assert_eq!(client.get_random_asset(), None);
+        
+        let asset_code = String::from_str(&env, "USDC");
+        client.register_asset(&asset_code, &Address::generate(&env), &1000, &String::from_str(&env, "USD Coin"));
+        for _ in 0..10 {
+            assert_eq!(client.get_random_asset().unwrap().asset_code, asset_code);
+        }
+    }
+    
+    #[test]
+    fn test_event_seq_increments_per_event() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        assert_eq!(client.get_event_seq(), 0);
+        
+        // Each call below publishes exactly one event carrying the new sequence number
+        let last_event_seq = || {
+            let (_, _, data) = env.events().all().last().unwrap();
+            let (seq, _): (u64, Val) = data.into_val(&env);
+            seq
+        };
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        assert_eq!(last_event_seq(), 1);
+        assert_eq!(client.get_event_seq(), 1);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        assert_eq!(last_event_seq(), 2);
+        assert_eq!(client.get_event_seq(), 2);
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_eq!(last_event_seq(), 3);
+        assert_eq!(client.get_event_seq(), 3);
+        client.deprecate_asset(&eurc, &usdc);
+        assert_eq!(last_event_seq(), 4);
+        assert_eq!(client.get_event_seq(), 4);
+        client.remove_asset(&eurc);
+        assert_eq!(last_event_seq(), 5);
+        assert_eq!(client.get_event_seq(), 5);
+        
+        // Reads publish nothing
+        client.get_asset_info(&usdc);
+        assert_eq!(client.get_event_seq(), 5);
+    }
+    
+    #[test]
+    fn test_reserve_then_activate_registration() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let other = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        
+        // The placeholder is inactive and keeps other issuers off the code
+        client.reserve_registration(&usdc, &issuer);
+        let placeholder = client.get_asset_info(&usdc).unwrap();
+        assert!(placeholder.reserved);
+        assert!(!placeholder.is_active);
+        assert_eq!(client.get_active_asset_count(), 0);
+        assert_eq!(client.can_register(&usdc, &other, &1000), RegisterResult::AlreadyExists);
+        assert!(client.try_register_asset(&usdc, &other, &1000, &description).is_err());
+        
+        env.ledger().set_timestamp(500);
+        client.activate_registration(&usdc, &1000, &description);
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert!(asset.is_active);
+        assert!(!asset.reserved);
+        assert_eq!(asset.issuer, issuer);
+        assert_eq!(asset.total_supply, 1000);
+        assert_eq!(asset.registration_time, 500);
+        assert_eq!(client.get_active_asset_count(), 1);
+        assert_eq!(client.get_aggregate_supply(), 1000);
+        assert_eq!(client.get_asset_index(&usdc), Some(0));
+        
+        // A completed registration cannot be activated again
+        assert!(client.try_activate_registration(&usdc, &2000, &description).is_err());
+    }
+    
+    #[test]
+    fn test_activate_registration_rejects_other_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let other = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        client.reserve_registration(&usdc, &issuer);
+        
+        // Only the other issuer signs, so the reserving issuer's auth is missing
+        env.mock_auths(&[MockAuth {
+            address: &other,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "activate_registration",
+                args: (usdc.clone(), 1000i128, description.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        assert!(client.try_activate_registration(&usdc, &1000, &description).is_err());
+        assert!(client.get_asset_info(&usdc).unwrap().reserved);
+        assert_eq!(client.get_active_asset_count(), 0);
+    }
+    
+    #[test]
+    fn test_scans_page_through_the_whole_index() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        
+        // Codes A000..A104 fill slots past MAX_SCAN; only the last one is later changed
+        let code_at = |n: u64| {
+            let digits = [b'0' + (n / 100) as u8, b'0' + (n / 10 % 10) as u8, b'0' + (n % 10) as u8];
+            let mut bytes = [b'A', 0, 0, 0];
+            bytes[1..].copy_from_slice(&digits);
+            String::from_bytes(&env, &bytes)
+        };
+        let slots = MAX_SCAN + 5;
+        for n in 0..slots {
+            client.register_asset(&code_at(n), &issuer, &1000, &description);
+        }
+        let last = code_at(slots - 1);
+        env.ledger().set_timestamp(15);
+        client.update_asset_supply(&issuer, &last, &5);
+        
+        // Each call reads one window and hands back where the next one starts
+        let (updated, next) = client.get_assets_updated_since(&15, &0, &10);
+        assert_eq!((updated.len(), next), (0, MAX_SCAN));
+        let (updated, next) = client.get_assets_updated_since(&15, &next, &10);
+        assert_eq!((updated.len(), next), (1, slots));
+        assert_eq!(updated.get(0).unwrap().asset_code, last);
+        
+        assert_eq!(client.list_assets_by_supply_range(&0, &10, &0, &10), (Vec::new(&env), MAX_SCAN));
+        assert_eq!(client.list_assets_by_supply_range(&0, &10, &MAX_SCAN, &10).0.len(), 1);
+        
+        let folded = String::from_str(&env, "a104");
+        assert_eq!(client.get_asset_info_ci(&folded, &0), (None, MAX_SCAN));
+        let (found, _) = client.get_asset_info_ci(&folded, &MAX_SCAN);
+        assert_eq!(found.unwrap().total_supply, 5);
+        
+        assert_eq!(client.check_invariants(&0), (None, MAX_SCAN));
+        assert_eq!(client.check_invariants(&MAX_SCAN), (None, slots));
+        
+        // A full page stops early and resumes at the first slot it did not read
+        let (updated, next) = client.get_assets_updated_since(&0, &0, &3);
+        assert_eq!((updated.len(), next), (3, 3));
+        assert_eq!(client.get_assets_updated_since(&0, &next, &1).0.get(0).unwrap().asset_code, code_at(3));
+        assert_eq!(client.get_assets_updated_since(&0, &slots, &10), (Vec::new(&env), slots));
+        
+        // Oversized limits are clamped to a page
+        let (updated, next) = client.get_assets_updated_since(&0, &0, &u32::MAX);
+        assert_eq!((updated.len(), next), (MAX_PAGE_SIZE, MAX_PAGE_SIZE as u64));
+        
+        // A zero limit still returns one result, so following the cursor always terminates
+        let (updated, next) = client.get_assets_updated_since(&0, &0, &0);
+        assert_eq!((updated.len(), next), (1, 1));
+        assert_eq!(client.list_assets_by_supply_range(&0, &1000, &0, &0).1, 1);
+        assert_eq!(client.list_unverified_issuer_assets(&0, &0), (Vec::new(&env), MAX_SCAN));
+        assert_eq!(client.assets_near_expiry(&u32::MAX, &0, &0).1, 1);
+    }
+    
+    #[test]
+    fn test_get_assets_by_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let alice = Address::generate(&env);
+        let bob = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let gbpc = String::from_str(&env, "GBPC");
+        
+        // An issuer with nothing registered gets an empty list
+        assert_eq!(client.get_assets_by_issuer(&alice).len(), 0);
+        assert_eq!(client.get_issuer_asset_count(&alice), 0);
+        
+        client.register_asset(&usdc, &alice, &1000, &description);
+        client.register_asset(&eurc, &alice, &1000, &description);
+        client.reserve_registration(&gbpc, &bob);
+        client.activate_registration(&gbpc, &1000, &description);
+        assert!(client.try_register_asset(&usdc, &alice, &1000, &description).is_err());
+        
+        let mut expected = Vec::new(&env);
+        expected.push_back(usdc.clone());
+        expected.push_back(eurc.clone());
+        assert_eq!(client.get_assets_by_issuer(&alice), expected);
+        assert_eq!(client.get_issuer_asset_count(&alice), 2);
+        
+        // Reserving then activating lists the code once
+        let mut expected = Vec::new(&env);
+        expected.push_back(gbpc.clone());
+        assert_eq!(client.get_assets_by_issuer(&bob), expected);
+        
+        // A transfer moves the code between the two lists
+        client.transfer_issuer(&usdc, &bob);
+        assert_eq!(client.get_issuer_asset_count(&alice), 1);
+        assert_eq!(client.get_assets_by_issuer(&bob).get(1), Some(usdc));
+    }
+    
+    #[test]
+    fn test_get_issuer_summary() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let other = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &500, &description);
+        client.register_asset(&String::from_str(&env, "GBPC"), &issuer, &200, &description);
+        client.register_asset(&String::from_str(&env, "OTHER"), &other, &9999, &description);
+        client.deprecate_asset(&eurc, &usdc);
+        client.adjust_reputation(&issuer, &25);
+        
+        // The deprecated asset still counts as registered but not as active
+        let summary = client.get_issuer_summary(&issuer);
+        assert_eq!(summary.asset_count, 3);
+        assert_eq!(summary.active_count, 2);
+        assert_eq!(summary.aggregate_supply, 1200);
+        assert_eq!(summary.reputation, 25);
+        
+        client.remove_asset(&usdc);
+        let summary = client.get_issuer_summary(&issuer);
+        assert_eq!((summary.asset_count, summary.active_count, summary.aggregate_supply), (2, 1, 200));
+        
+        let unknown = client.get_issuer_summary(&Address::generate(&env));
+        assert_eq!((unknown.asset_count, unknown.active_count, unknown.aggregate_supply, unknown.reputation), (0, 0, 0, 0));
+    }
+    
+    #[test]
+    fn test_register_asset_idempotent_retry() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let key = BytesN::from_array(&env, &[7; 32]);
+        
+        assert!(client.register_asset_idempotent(&usdc, &issuer, &1000, &description, &key));
+        let first = client.get_asset_info(&usdc).unwrap();
+        
+        // The used key is kept in temporary storage for the retry window only
+        env.as_contract(&client.address, || {
+            let entry = AssetBook::Idempotency(issuer.clone(), key.clone());
+            assert!(!env.storage().instance().has(&entry));
+            assert_eq!(env.storage().temporary().get(&entry), Some(usdc.clone()));
+            assert_eq!(env.storage().temporary().get_ttl(&entry), IDEMPOTENCY_TTL);
+        });
+        
+        // The retry succeeds without re-registering or bumping counters
+        assert!(client.register_asset_idempotent(&usdc, &issuer, &1000, &description, &key));
+        assert_eq!(client.get_asset_info(&usdc), Some(first));
+        assert_eq!(client.get_total_assets(), 1);
+        
+        // A fresh key still hits the duplicate check, and a used key cannot claim another code
+        let other_key = BytesN::from_array(&env, &[8; 32]);
+        assert!(client.try_register_asset_idempotent(&usdc, &issuer, &1000, &description, &other_key).is_err());
+        let eurc = String::from_str(&env, "EURC");
+        assert!(client.try_register_asset_idempotent(&eurc, &issuer, &1000, &description, &key).is_err());
+        
+        // Keys are scoped per issuer
+        let other = Address::generate(&env);
+        assert!(client.register_asset_idempotent(&eurc, &other, &1000, &description, &key));
+        
+        // A key does not outlive the asset it created: after removal the retry registers again
+        client.remove_asset(&usdc);
+        assert!(client.register_asset_idempotent(&usdc, &issuer, &1000, &description, &key));
+        assert!(client.get_asset_info(&usdc).is_some());
+        assert_eq!(client.get_total_assets(), 2);
+    }
+    
+    #[test]
+    fn test_register_verified_asset_by_registrar() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let registrar = Address::generate(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        // Not yet a registrar
+        assert!(client.try_register_verified_asset(&registrar, &usdc, &issuer, &1000, &description).is_err());
+        assert!(client.get_asset_info(&usdc).is_none());
+        
+        client.add_registrar(&registrar);
+        client.register_verified_asset(&registrar, &usdc, &issuer, &1000, &description);
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert!(asset.is_active);
+        assert!(asset.is_verified);
+        assert_eq!(asset.issuer, issuer);
+        
+        client.remove_registrar(&registrar);
+        assert!(client.try_register_verified_asset(&registrar, &eurc, &issuer, &1000, &description).is_err());
+    }
+    
+    #[test]
+    fn test_get_supply_history_page() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        
+        // Registration plus 11 updates gives 12 checkpoints, supply 0..=11 at time 10 * n
+        client.register_asset(&usdc, &issuer, &0, &String::from_str(&env, "Test asset"));
+        for n in 1..12u64 {
+            env.ledger().set_timestamp(10 * n);
+            client.update_asset_supply(&issuer, &usdc, &(n as i128));
+        }
+        
+        let page = client.get_supply_history_page(&usdc, &0, &5);
+        assert_eq!(page.len(), 5);
+        assert_eq!(page.get(0).unwrap(), (0, 0));
+        assert_eq!(page.get(4).unwrap(), (40, 4));
+        
+        let page = client.get_supply_history_page(&usdc, &5, &5);
+        assert_eq!(page.get(0).unwrap(), (50, 5));
+        
+        // The last page is partial, and offsets past the end return nothing
+        let page = client.get_supply_history_page(&usdc, &10, &5);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page.get(1).unwrap(), (110, 11));
+        assert_eq!(client.get_supply_history_page(&usdc, &12, &5).len(), 0);
+        assert_eq!(client.get_supply_history_page(&usdc, &100, &5).len(), 0);
+        assert_eq!(client.get_supply_history_page(&String::from_str(&env, "NONE"), &0, &5).len(), 0);
+    }
     
-    // Function to register a new asset on the explorer
-    pub fn register_asset(
-        env: Env, 
-        asset_code: String, 
-        issuer: Address,
-        total_supply: i128,
-        description: String
-    ) -> bool {
+    #[test]
+    fn test_supply_events_and_capped_history() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let last_event = || env.events().all().last().unwrap();
+        
+        // Registration publishes the issuer, supply and time, and is the first checkpoint
+        env.ledger().set_timestamp(100);
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        let (_, topics, data) = last_event();
+        assert_eq!(topics, (symbol_short!("register"), usdc.clone()).into_val(&env));
+        let (_, payload): (u64, (Address, i128, u64)) = data.into_val(&env);
+        assert_eq!(payload, (issuer.clone(), 1000, 100));
+        assert_eq!(client.get_supply_history(&usdc), Vec::from_array(&env, [SupplyCheckpoint { timestamp: 100, supply: 1000 }]));
         
-        // Check if asset already exists
-        let existing_asset = Self::get_asset_info(env.clone(), asset_code.clone());
+        // An update to the same value is still published and recorded
+        env.ledger().set_timestamp(110);
+        client.update_asset_supply(&issuer, &usdc, &1000);
+        let (_, topics, data) = last_event();
+        assert_eq!(topics, (symbol_short!("supply"), usdc.clone()).into_val(&env));
+        let (_, payload): (u64, (i128, i128, u64)) = data.into_val(&env);
+        assert_eq!(payload, (1000, 1000, 110));
+        assert_eq!(client.get_supply_history(&usdc).len(), 2);
         
-        if existing_asset.is_active {
-            log!(&env, "Asset already registered: {}", asset_code);
-            panic!("Asset already exists!");
+        // Only the last MAX_HISTORY checkpoints are kept, oldest dropped first
+        for step in 0..25u64 {
+            env.ledger().set_timestamp(200 + step);
+            client.update_asset_supply(&issuer, &usdc, &(2000 + step as i128));
         }
+        let history = client.get_supply_history(&usdc);
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.first().unwrap(), SupplyCheckpoint { timestamp: 205, supply: 2005 });
+        assert_eq!(history.last().unwrap(), SupplyCheckpoint { timestamp: 224, supply: 2024 });
         
-        // Get current timestamp
-        let time = env.ledger().timestamp();
+        // Times before the dropped checkpoints are reported as truncated, not unknown
+        assert_eq!(client.try_get_supply_at(&usdc, &150), Err(Ok(Error::HistoryTruncated)));
+        assert_eq!(client.try_get_twas(&usdc, &100, &300), Err(Ok(Error::HistoryTruncated)));
+        assert_eq!(client.try_get_supply_at(&usdc, &210), Ok(Ok(Some(2010))));
+        assert_eq!(client.try_get_twas(&usdc, &205, &207), Ok(Ok(Some(2005))));
         
-        // Create new asset info
-        let new_asset = AssetInfo {
-            asset_code: asset_code.clone(),
-            issuer: issuer.clone(),
-            total_supply,
-            description,
-            is_active: true,
-            registration_time: time,
-        };
+        assert_eq!(client.get_supply_history(&String::from_str(&env, "NONE")).len(), 0);
+    }
+    
+    #[test]
+    fn test_register_rejects_sentinel_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let sentinel = sentinel_issuer(&env);
         
-        // Store asset information
-        env.storage().instance().set(&AssetBook::Asset(asset_code.clone()), &new_asset);
+        assert_eq!(client.can_register(&usdc, &sentinel, &1000), RegisterResult::InvalidIssuer);
+        assert_eq!(client.try_register_asset(&usdc, &sentinel, &1000, &description), Err(Ok(Error::InvalidIssuer)));
+        assert!(client.get_asset_info(&usdc).is_none());
         
-        // Update asset count
-        let mut count: u64 = env.storage().instance().get(&ASSET_COUNT).unwrap_or(0);
-        count += 1;
-        env.storage().instance().set(&ASSET_COUNT, &count);
+        client.register_asset(&usdc, &Address::generate(&env), &1000, &description);
+    }
+    
+    #[test]
+    fn test_export_all_includes_inactive() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let gbpc = String::from_str(&env, "GBPC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.register_asset(&gbpc, &issuer, &1000, &description);
+        client.deprecate_asset(&eurc, &usdc);
         
-        env.storage().instance().extend_ttl(5000, 5000);
+        // list_assets hides the deprecated record, the export keeps it in index order
+        assert_eq!(client.list_assets(&0, &10).len(), 2);
+        let exported = client.export_all(&0, &10);
+        assert_eq!(exported.len(), 3);
+        assert_eq!(exported.get(0).unwrap().asset_code, usdc);
+        assert_eq!(exported.get(1).unwrap().asset_code, eurc);
+        assert!(!exported.get(1).unwrap().is_active);
+        assert_eq!(exported.get(2).unwrap().asset_code, gbpc);
         
-        log!(&env, "Asset registered successfully: {}", asset_code);
-        true
+        let page = client.export_all(&1, &1);
+        assert_eq!(page.len(), 1);
+        assert_eq!(page.get(0).unwrap().asset_code, eurc);
+        assert_eq!(client.export_all(&3, &10).len(), 0);
     }
     
-    // Function to retrieve asset information by asset code
-    pub fn get_asset_info(env: Env, asset_code: String) -> AssetInfo {
-        let key = AssetBook::Asset(asset_code.clone());
+    #[test]
+    fn test_code_reuse_policy() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let newcomer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.deprecate_asset(&eurc, &usdc);
+        
+        // Reuse is off by default, so the inactive code stays with its record
+        assert_eq!(client.can_register(&eurc, &newcomer, &1000), RegisterResult::AlreadyExists);
+        assert!(client.try_register_asset(&eurc, &newcomer, &1000, &description).is_err());
+        assert_eq!(client.get_asset_info(&eurc).unwrap().issuer, issuer);
         
-        env.storage().instance().get(&key).unwrap_or(AssetInfo {
-            asset_code: String::from_str(&env, "NOT_FOUND"),
-            issuer: Address::from_string(&String::from_str(&env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF")),
-            total_supply: 0,
-            description: String::from_str(&env, "Asset not found"),
-            is_active: false,
-            registration_time: 0,
-        })
+        // With reuse a new issuer may claim it
+        client.set_allow_code_reuse(&true);
+        client.register_asset(&eurc, &newcomer, &1000, &description);
+        let asset = client.get_asset_info(&eurc).unwrap();
+        assert!(asset.is_active);
+        assert_eq!(asset.issuer, newcomer);
     }
     
-    // Function to update asset supply
-    pub fn update_asset_supply(env: Env, asset_code: String, new_supply: i128) -> bool {
-        let mut asset = Self::get_asset_info(env.clone(), asset_code.clone());
+    #[test]
+    fn test_supply_alert_threshold() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &10_000, &String::from_str(&env, "Test asset"));
+        client.set_alert_threshold_bps(&1000);
         
-        if !asset.is_active {
-            log!(&env, "Asset not found: {}", asset_code);
-            panic!("Asset does not exist!");
+        let alert_topics = (symbol_short!("alert"), usdc.clone()).into_val(&env);
+        let alerts = || env.events().all().iter().filter(|(_, topics, _)| *topics == alert_topics).count();
+        
+        // A 5% change stays under the 10% threshold
+        client.update_asset_supply(&issuer, &usdc, &10_500);
+        assert_eq!(alerts(), 0);
+        
+        // A drop of over 50% raises an alert carrying the old and new supply
+        client.update_asset_supply(&issuer, &usdc, &5_000);
+        assert_eq!(alerts(), 1);
+        let (_, _, data) = env.events().all().last().unwrap();
+        let (_, payload): (u64, (i128, i128)) = data.into_val(&env);
+        assert_eq!(payload, (10_500, 5_000));
+    }
+    
+    #[test]
+    fn test_scalar_getters() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let description = String::from_str(&env, "USD Coin");
+        let usdc = String::from_str(&env, "USDC");
+        let missing = String::from_str(&env, "NONE");
+        client.register_asset(&usdc, &Address::generate(&env), &1000, &description);
+        
+        assert_eq!(client.get_description(&usdc), Some(description));
+        assert_eq!(client.get_total_supply(&usdc), Some(1000));
+        assert_eq!(client.get_is_active(&usdc), Some(true));
+        
+        assert_eq!(client.get_description(&missing), None);
+        assert_eq!(client.get_total_supply(&missing), None);
+        assert_eq!(client.get_is_active(&missing), None);
+    }
+    
+    #[test]
+    fn test_clear_registry_blocks_registration_until_done() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let gbpc = String::from_str(&env, "GBPC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        
+        // A partial clear leaves the flag on and registrations rejected
+        assert!(!client.clear_registry(&1));
+        assert_eq!(client.get_total_assets(), 1);
+        assert_eq!(client.can_register(&gbpc, &issuer, &1000), RegisterResult::Clearing);
+        assert!(client.try_register_asset(&gbpc, &issuer, &1000, &description).is_err());
+        
+        assert!(client.clear_registry(&1));
+        assert_eq!(client.get_total_assets(), 0);
+        assert_eq!(client.get_active_asset_count(), 0);
+        assert_eq!(client.get_aggregate_supply(), 0);
+        assert!(client.get_asset_info(&usdc).is_none());
+        
+        client.register_asset(&gbpc, &issuer, &1000, &description);
+        assert_eq!(client.get_asset_index(&gbpc), Some(0));
+    }
+    
+    #[test]
+    fn test_register_rejected_while_clearing_flag_set() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let eurc = String::from_str(&env, "EURC");
+        client.reserve_registration(&eurc, &issuer);
+        env.as_contract(&client.address, || {
+            env.storage().instance().set(&CLEARING, &true);
+        });
+        
+        let result = client.try_register_asset(
+            &String::from_str(&env, "USDC"),
+            &Address::generate(&env),
+            &1000,
+            &String::from_str(&env, "Test asset"),
+        );
+        assert_eq!(result, Err(Ok(Error::RegistryClearing)));
+        
+        // Activating a reservation counts as a registration, so it waits too
+        let description = String::from_str(&env, "Test asset");
+        assert!(client.try_activate_registration(&eurc, &1000, &description).is_err());
+        env.as_contract(&client.address, || {
+            env.storage().instance().remove(&CLEARING);
+        });
+        client.activate_registration(&eurc, &1000, &description);
+        assert!(client.get_asset_info(&eurc).unwrap().is_active);
+    }
+    
+    #[test]
+    fn test_is_supply_capped() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let capped = String::from_str(&env, "FIXED");
+        let mintable = String::from_str(&env, "MINT");
+        client.register_asset(&capped, &issuer, &1000, &description);
+        client.register_asset(&mintable, &issuer, &1000, &description);
+        
+        // The cap can't sit below the current supply, and updates can't exceed it
+        assert!(client.try_set_max_supply(&capped, &Some(999)).is_err());
+        client.set_max_supply(&capped, &Some(5000));
+        assert!(client.try_update_asset_supply(&issuer, &capped, &5001).is_err());
+        client.update_asset_supply(&issuer, &capped, &5000);
+        
+        assert_eq!(client.is_supply_capped(&capped), Some(true));
+        assert_eq!(client.is_supply_capped(&mintable), Some(false));
+        assert_eq!(client.is_supply_capped(&String::from_str(&env, "NONE")), None);
+        
+        client.set_max_supply(&capped, &None);
+        assert_eq!(client.is_supply_capped(&capped), Some(false));
+    }
+    
+    #[test]
+    fn test_random_operation_sequences_keep_invariants() {
+        let codes = ["A", "B", "C", "D", "E"];
+        
+        for seed in [1u64, 42, 2024] {
+            let env = Env::default();
+            env.mock_all_auths();
+            let client = setup(&env);
+            let issuers = [Address::generate(&env), Address::generate(&env)];
+            let description = String::from_str(&env, "Test asset");
+            
+            // Small LCG so every run replays the same operation sequence
+            let mut state = seed;
+            let mut next = |bound: u64| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) % bound
+            };
+            
+            for _ in 0..40 {
+                let code = String::from_str(&env, codes[next(5) as usize]);
+                let issuer = &issuers[next(2) as usize];
+                let supply = next(1000) as i128;
+                
+                // Operations that are invalid in the current state just fail
+                match next(6) {
+                    0 | 1 => { let _ = client.try_register_asset(&code, issuer, &supply, &description); }
+                    2 => { let _ = client.try_register_asset_pending_supply(&code, issuer, &description); }
+                    3 => {
+                        if let Some(asset) = client.get_asset_info(&code) {
+                            let _ = client.try_update_asset_supply(&asset.issuer, &code, &supply);
+                        }
+                    }
+                    4 => {
+                        let successor = String::from_str(&env, codes[next(5) as usize]);
+                        let _ = client.try_deprecate_asset(&code, &successor);
+                    }
+                    _ => {
+                        if client.try_remove_asset(&code).is_ok() {
+                            assert_eq!(client.get_asset_index(&code), None);
+                        }
+                    }
+                }
+                
+                assert_eq!(client.check_invariants(&0).0, None);
+                assert!(client.get_active_asset_count() <= client.get_total_assets());
+            }
         }
+    }
+    
+    #[test]
+    fn test_check_invariants_detects_desync() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        client.register_asset(&String::from_str(&env, "USDC"), &Address::generate(&env), &1000, &String::from_str(&env, "Test asset"));
+        assert_eq!(client.check_invariants(&0).0, None);
         
-        asset.total_supply = new_supply;
+        env.as_contract(&client.address, || {
+            env.storage().instance().set(&AGG_SUPPLY, &I256::from_i128(&env, 1));
+        });
+        assert_eq!(client.check_invariants(&0).0, Some(symbol_short!("SUPPLY")));
+    }
+    
+    #[test]
+    fn test_get_asset_at_index() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let codes = [String::from_str(&env, "USDC"), String::from_str(&env, "EURC"), String::from_str(&env, "GBPC")];
+        for code in codes.iter() {
+            client.register_asset(code, &issuer, &1000, &description);
+        }
         
-        env.storage().instance().set(&AssetBook::Asset(asset_code.clone()), &asset);
-        env.storage().instance().extend_ttl(5000, 5000);
+        for (index, code) in codes.iter().enumerate() {
+            assert_eq!(client.get_asset_at_index(&(index as u64)).unwrap().asset_code, *code);
+        }
         
-        log!(&env, "Asset supply updated for: {}", asset_code);
-        true
+        client.remove_asset(&codes[1]);
+        assert_eq!(client.get_asset_at_index(&1), None);
+        assert_eq!(client.get_asset_at_index(&3), None);
     }
     
-    // Function to get total number of registered assets
-    pub fn get_total_assets(env: Env) -> u64 {
-        env.storage().instance().get(&ASSET_COUNT).unwrap_or(0)
+    #[test]
+    fn test_padded_code_collides_with_canonical() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let padded = String::from_str(&env, " USDC ");
+        
+        // The padded variant is stored under the canonical code
+        client.register_asset(&padded, &issuer, &1000, &description);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().asset_code, usdc);
+        assert!(client.try_register_asset(&usdc, &issuer, &1000, &description).is_err());
+        assert!(client.try_register_asset(&String::from_str(&env, "USDC "), &issuer, &1000, &description).is_err());
+        assert_eq!(client.get_total_assets(), 1);
+        
+        // Reads, updates and removal accept either form
+        assert_eq!(client.get_asset_info(&padded).unwrap().asset_code, usdc);
+        client.update_asset_supply(&issuer, &padded, &2000);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 2000);
+        assert_eq!(client.get_total_supply(&padded), Some(2000));
+        assert_eq!(client.get_description(&padded), Some(description.clone()));
+        assert_eq!(client.get_is_active(&padded), Some(true));
+        assert_eq!(client.get_asset_hash(&padded), client.get_asset_hash(&usdc));
+        assert_eq!(client.get_asset_index(&padded), Some(0));
+        assert_eq!(client.get_supply_history_page(&padded, &0, &10).len(), 2);
+        assert_eq!(client.get_supply_at(&padded, &u64::MAX), Some(2000));
+        client.set_delegate(&padded, &issuer);
+        assert_eq!(client.get_delegate(&usdc), Some(issuer.clone()));
+        assert_eq!(client.refresh_ttls(&Vec::from_array(&env, [padded.clone()])), 1);
+        let uri = String::from_str(&env, "https://example.com/usdc.json");
+        assert_eq!(client.set_metadata_uris_batch(&issuer, &Vec::from_array(&env, [(padded.clone(), uri.clone())])), 1);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().metadata_uri, Some(uri));
+        client.remove_asset(&padded);
+        assert_eq!(client.get_total_assets(), 0);
     }
-}
-
-#[cfg(test)]
-mod test {
-    use super::*;
-    use soroban_sdk::Env;
-
+    
     #[test]
-    fn test_register_and_get_asset() {
+    fn test_get_asset_value_from_oracle() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let usdc = String::from_str(&env, "USDC");
+        
+        // 1000 whole units at the default 7 decimals
+        client.register_asset(&usdc, &Address::generate(&env), &10_000_000_000, &String::from_str(&env, "Test asset"));
+        assert_eq!(client.get_asset_value(&usdc), None);
+        
+        let oracle_id = env.register(MockOracle, ());
+        let oracle = MockOracleClient::new(&env, &oracle_id);
+        oracle.set_price(&250);
+        client.set_price_oracle(&usdc, &Some(oracle_id.clone()));
+        assert_eq!(client.get_asset_value(&usdc), Some(250_000));
+        
+        // A price large enough to overflow the value yields None instead of a panic
+        oracle.set_price(&i128::MAX);
+        assert_eq!(client.get_asset_value(&usdc), None);
+        
+        // A value near the limit is exact even though supply times price is not an i128
+        let whale = String::from_str(&env, "WHALE");
+        client.register_asset(&whale, &Address::generate(&env), &10i128.pow(31), &String::from_str(&env, "Test asset"));
+        client.set_price_oracle(&whale, &Some(oracle_id.clone()));
+        oracle.set_price(&100_000_000);
+        assert_eq!(client.get_asset_value(&whale), Some(10i128.pow(32)));
+        
+        client.set_price_oracle(&usdc, &None);
+        assert_eq!(client.get_asset_value(&usdc), None);
+        assert_eq!(client.get_asset_value(&String::from_str(&env, "NONE")), None);
+    }
+    
+    #[test]
+    fn test_list_assets_by_verification() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let codes = ["USDC", "EURC", "GBPC", "JPYC"].map(|code| String::from_str(&env, code));
+        for code in codes.iter() {
+            client.register_asset(code, &issuer, &1000, &description);
+        }
+        client.set_verified(&codes[0], &true);
+        client.set_verified(&codes[2], &true);
+        client.set_verified(&codes[3], &true);
+        client.deprecate_asset(&codes[3], &codes[0]);
+        
+        // Inactive assets are left out of both filters
+        let verified = client.list_assets_by_verification(&true, &0, &10);
+        assert_eq!(verified.len(), 2);
+        assert_eq!(verified.get(0).unwrap().asset_code, codes[0]);
+        assert_eq!(verified.get(1).unwrap().asset_code, codes[2]);
+        
+        let unverified = client.list_assets_by_verification(&false, &0, &10);
+        assert_eq!(unverified.len(), 1);
+        assert_eq!(unverified.get(0).unwrap().asset_code, codes[1]);
+        
+        assert_eq!(client.list_assets_by_verification(&true, &1, &2).len(), 1);
+        
+        // Requiring verification for listing hides unverified assets here as well
+        client.set_require_verified_for_listing(&true);
+        assert_eq!(client.list_assets_by_verification(&false, &0, &10).len(), 0);
+        assert_eq!(client.list_assets_by_verification(&true, &0, &10).len(), 2);
+        assert_eq!(client.list_assets(&0, &10).len(), 2);
+    }
+    
+    #[test]
+    fn test_retire_asset() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&String::from_str(&env, "EURC"), &issuer, &500, &description);
+        
+        env.ledger().set_timestamp(100);
+        client.retire_asset(&usdc);
+        let mut topics: Vec<Symbol> = Vec::new(&env);
+        for (_, event_topics, _) in env.events().all().iter() {
+            topics.push_back(event_topics.get(0).unwrap().into_val(&env));
+        }
+        assert_eq!(topics, Vec::from_array(&env, [symbol_short!("supply"), symbol_short!("retire")]));
+        
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert_eq!(asset.total_supply, 0);
+        assert!(!asset.is_active);
+        assert_eq!(client.get_active_asset_count(), 1);
+        assert_eq!(client.get_aggregate_supply(), 500);
+        assert_eq!(client.get_supply_at(&usdc, &100), Some(0));
+        
+        assert!(client.try_retire_asset(&usdc).is_err());
+    }
+    
+    #[test]
+    fn test_admin_deactivate_and_reactivate() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        
+        // Nobody but the admin may change an asset's lifecycle or verification
+        env.mock_auths(&[]);
+        assert!(client.try_deactivate_asset(&usdc).is_err());
+        assert!(client.try_set_verified(&usdc, &true).is_err());
+        env.mock_all_auths();
+        
+        client.deactivate_asset(&usdc);
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert!(!asset.is_active);
+        assert_eq!(asset.total_supply, 1000);
+        assert_eq!(client.get_active_asset_count(), 0);
+        assert_eq!(client.get_aggregate_supply(), 0);
+        assert_eq!(client.try_update_asset_supply(&issuer, &usdc, &2000), Err(Ok(Error::AssetInactive)));
+        assert!(client.try_deactivate_asset(&usdc).is_err());
+        
+        // Under the default config nobody else can claim the delisted code
+        let squatter = Address::generate(&env);
+        assert_eq!(
+            client.try_register_asset(&usdc, &squatter, &5, &description),
+            Err(Ok(Error::AssetAlreadyExists))
+        );
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert_eq!((asset.issuer, asset.is_active, asset.total_supply), (issuer.clone(), false, 1000));
+        
+        env.mock_auths(&[]);
+        assert!(client.try_reactivate_asset(&usdc).is_err());
+        env.mock_all_auths();
+        
+        client.reactivate_asset(&usdc);
+        assert!(client.get_asset_info(&usdc).unwrap().is_active);
+        assert_eq!(client.get_active_asset_count(), 1);
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_eq!(client.get_aggregate_supply(), 2000);
+        assert_eq!(client.check_invariants(&0).0, None);
+        assert!(client.try_reactivate_asset(&usdc).is_err());
+    }
+    
+    #[test]
+    fn test_event_level() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        assert_eq!(client.get_event_level(), EVENTS_ALL);
+        
+        client.set_event_level(&EVENTS_NONE);
+        client.register_asset(&String::from_str(&env, "USDC"), &issuer, &1000, &description);
+        assert_eq!(env.events().all().len(), 0);
+        assert_eq!(client.get_event_seq(), 0);
+        
+        client.set_event_level(&EVENTS_STATE);
+        let eurc = String::from_str(&env, "EURC");
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        let (_, topics, _) = env.events().all().last().unwrap();
+        assert_eq!(topics, (symbol_short!("register"), eurc).into_val(&env));
+        assert_eq!(client.get_event_seq(), 1);
+        
+        assert!(client.try_set_event_level(&3).is_err());
+    }
+    
+    #[test]
+    fn test_assets_near_expiry() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let gbpc = String::from_str(&env, "GBPC");
+        
+        // With the default 5000-ledger threshold, USDC lives until at least 5000
+        // and EURC until 8000; GBPC's bound is wiped to look like an old entry
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        env.ledger().set_sequence_number(3000);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        client.register_asset(&gbpc, &issuer, &1000, &description);
+        env.as_contract(&client.address, || {
+            env.storage().persistent().remove(&AssetBook::LiveUntil(gbpc.clone()));
+        });
+        
+        env.ledger().set_sequence_number(4500);
+        assert_eq!(client.assets_near_expiry(&1000, &0, &10).0, Vec::from_array(&env, [usdc.clone(), gbpc.clone()]));
+        
+        // Reading an asset extends its entries but records nothing
+        let live_until = || env.as_contract(&client.address, || {
+            env.storage().persistent().get::<_, u32>(&AssetBook::LiveUntil(usdc.clone()))
+        });
+        client.get_asset_info(&usdc);
+        assert_eq!(live_until(), Some(5000));
+        assert_eq!(client.assets_near_expiry(&4000, &0, &10).0.len(), 3);
+        assert_eq!(client.assets_near_expiry(&4000, &0, &1).0.len(), 1);
+        
+        // Writing to an asset pushes its bound out
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_eq!(client.assets_near_expiry(&1000, &0, &10).0, Vec::from_array(&env, [gbpc]));
+    }
+    
+    #[test]
+    fn test_refresh_ttls() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let instance_ttl = || env.as_contract(&client.address, || env.storage().instance().get_ttl());
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        
+        env.ledger().set_sequence_number(4500);
+        assert_eq!(instance_ttl(), 500);
+        assert_eq!(client.assets_near_expiry(&1000, &0, &10).0.len(), 2);
+        
+        // Missing codes are skipped and not counted
+        let codes = Vec::from_array(&env, [usdc.clone(), String::from_str(&env, "NONE"), eurc.clone()]);
+        assert_eq!(client.refresh_ttls(&codes), 2);
+        assert_eq!(instance_ttl(), 5000);
+        assert_eq!(client.assets_near_expiry(&1000, &0, &10).0.len(), 0);
+        
+        assert_eq!(client.refresh_ttls(&Vec::from_array(&env, [String::from_str(&env, "NONE")])), 0);
+        
+        // Nothing is refreshed while the TTL policy is disabled
+        client.set_ttl_config(&TtlConfig { enabled: false, threshold: 5000, extend_to: 5000 });
+        env.ledger().set_sequence_number(9000);
+        assert_eq!(client.refresh_ttls(&codes), 0);
+        assert_eq!(client.assets_near_expiry(&1000, &0, &10).0.len(), 2);
+    }
+    
+    #[test]
+    fn test_extend_asset_ttl() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let asset_ttl = |code: &String| env.as_contract(&client.address, || {
+            env.storage().persistent().get_ttl(&AssetBook::Asset(code.clone()))
+        });
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        assert_eq!(asset_ttl(&usdc), 5000);
+        
+        // Only the named asset's entries are extended
+        assert!(client.extend_asset_ttl(&usdc, &20_000));
+        assert_eq!(asset_ttl(&usdc), 20_000);
+        assert_eq!(asset_ttl(&eurc), 5000);
+        env.as_contract(&client.address, || {
+            let index_ttl = env.storage().persistent().get_ttl(&AssetBook::IndexOf(usdc.clone()));
+            assert_eq!(index_ttl, 20_000);
+        });
+        assert_eq!(client.assets_near_expiry(&10_000, &0, &10).0, Vec::from_array(&env, [eurc]));
+        
+        assert!(client.try_extend_asset_ttl(&String::from_str(&env, "NONE"), &20_000).is_err());
+        assert!(client.try_extend_asset_ttl(&usdc, &u32::MAX).is_err());
+    }
+    
+    #[test]
+    fn test_per_asset_entries_live_in_persistent_storage() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &String::from_str(&env, "Test asset"));
+        client.set_delegate(&usdc, &Address::generate(&env));
+        client.set_price_oracle(&usdc, &Some(Address::generate(&env)));
+        client.set_category(&usdc, &symbol_short!("stable"));
+        client.adjust_reputation(&issuer, &5);
+        client.set_issuer_allowed(&issuer, &true);
+        let registrar = Address::generate(&env);
+        client.add_registrar(&registrar);
+        
+        let keys = [
+            AssetBook::History(usdc.clone()),
+            AssetBook::Delegate(usdc.clone()),
+            AssetBook::PriceOracle(usdc.clone()),
+            AssetBook::Issuer(issuer.clone()),
+            AssetBook::Reputation(issuer.clone()),
+            AssetBook::CategoryCount(symbol_short!("stable")),
+            AssetBook::Allowed(issuer.clone()),
+            AssetBook::Registrar(registrar.clone()),
+        ];
+        env.as_contract(&client.address, || {
+            for key in keys.iter() {
+                assert!(!env.storage().instance().has(key));
+                assert!(env.storage().persistent().has(key));
+                assert_eq!(env.storage().persistent().get_ttl(key), 5000);
+            }
+        });
+        
+        // Keeping the asset alive keeps its per-asset data and issuer list alive too
+        client.extend_asset_ttl(&usdc, &20_000);
+        env.as_contract(&client.address, || {
+            for key in keys[..4].iter() {
+                assert_eq!(env.storage().persistent().get_ttl(key), 20_000);
+            }
+        });
+    }
+    
+    #[test]
+    fn test_legacy_instance_records_migrate_on_read() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let gbpc = String::from_str(&env, "GBPC");
+        
+        // Two first-release records, counted the way the first release counted them
+        env.as_contract(&client.address, || {
+            for (code, supply) in [(&usdc, 1000), (&eurc, 2000)] {
+                let legacy = LegacyAssetInfo {
+                    asset_code: code.clone(),
+                    issuer: issuer.clone(),
+                    total_supply: supply,
+                    description: String::from_str(&env, "Legacy asset"),
+                    is_active: true,
+                    registration_time: 42,
+                };
+                env.storage().instance().set(&AssetBook::Asset(code.clone()), &legacy);
+            }
+            env.storage().instance().set(&ASSET_COUNT, &2u64);
+        });
+        
+        let asset = client.get_asset_info(&usdc).unwrap();
+        assert_eq!(asset.total_supply, 1000);
+        assert_eq!(asset.registration_time, 42);
+        assert_eq!(asset.last_updated, 42);
+        assert!(!asset.is_verified);
+        assert_eq!(client.get_supply_at(&usdc, &42), Some(1000));
+        
+        // Until every record is migrated the counters can't be reconciled
+        assert_eq!(client.check_invariants(&0).0, Some(symbol_short!("COUNT")));
+        assert_eq!(client.list_assets(&0, &10).len(), 1);
+        client.get_asset_info(&eurc);
+        
+        let stored_in = |code: &String| env.as_contract(&client.address, || {
+            let key = AssetBook::Asset(code.clone());
+            (env.storage().instance().has(&key), env.storage().persistent().has(&key))
+        });
+        assert_eq!(stored_in(&usdc), (false, true));
+        assert_eq!(stored_in(&eurc), (false, true));
+        
+        // Migrated records are indexed, listed under their issuer and counted
+        let listed = client.list_assets(&0, &10);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed.get(0).unwrap().asset_code, usdc);
+        assert_eq!(listed.get(1).unwrap().asset_code, eurc);
+        assert_eq!(client.get_issuer_asset_count(&issuer), 2);
+        assert_eq!(client.get_total_assets(), 2);
+        assert_eq!(client.get_active_asset_count(), 2);
+        assert_eq!(client.get_aggregate_supply(), 3000);
+        assert_eq!(client.check_invariants(&0).0, None);
+        
+        // New registrations take the next slot, and later writes land in persistent storage
+        client.register_asset(&gbpc, &issuer, &500, &String::from_str(&env, "Test asset"));
+        assert_eq!(client.get_asset_index(&gbpc), Some(2));
+        client.update_asset_supply(&issuer, &usdc, &3000);
+        assert_eq!(stored_in(&usdc), (false, true));
+        let stored = env.as_contract(&client.address, || {
+            env.storage().persistent().get::<_, AssetInfo>(&AssetBook::Asset(usdc.clone()))
+        });
+        assert_eq!(stored.unwrap().total_supply, 3000);
+        
+        client.deactivate_asset(&usdc);
+        assert_eq!(client.get_aggregate_supply(), 2500);
+        assert_eq!(client.check_invariants(&0).0, None);
+    }
+    
+    #[test]
+    fn test_constructor_sets_config() {
         let env = Env::default();
-        let contract_id = env.register_contract(None, AssetExplorerContract);
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let ttl = TtlConfig { enabled: true, threshold: 1000, extend_to: 2000 };
+        let config = RegistryConfig { ttl: ttl.clone(), registration_mode: RegistrationMode::Allowlist };
+        let contract_id = env.register(AssetExplorerContract, (admin.clone(), config));
         let client = AssetExplorerContractClient::new(&env, &contract_id);
         
-        let asset_code = String::from_str(&env, "USDC");
-        let issuer = Address::from_string(&String::from_str(&env, "GAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAWHF"));
-        let description = String::from_str(&env, "USD Coin");
+        assert_eq!(client.get_schema_version(), SCHEMA_VERSION);
+        assert_eq!(client.get_ttl_config(), ttl);
+        assert_eq!(client.health(), (true, false));
         
-        // Register asset
-        let result = client.register_asset(&asset_code, &issuer, &1000000, &description);
-        assert_eq!(result, true);
+        // Allowlist mode is in force right away
+        let issuer = Address::generate(&env);
+        assert_eq!(client.can_register(&String::from_str(&env, "USDC"), &issuer, &1000), RegisterResult::Unauthorized);
         
-        // Get asset info
-        let asset_info = client.get_asset_info(&asset_code);
-        assert_eq!(asset_info.asset_code, asset_code);
-        assert_eq!(asset_info.total_supply, 1000000);
+        assert!(client.try_initialize(&Address::generate(&env)).is_err());
+    }
+    
+    #[test]
+    fn test_list_all_codes() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let codes = ["USDC", "EURC", "GBPC", "JPYC", "CHFC"].map(|code| String::from_str(&env, code));
+        for code in codes.iter() {
+            client.register_asset(code, &issuer, &1000, &description);
+        }
+        client.deprecate_asset(&codes[1], &codes[0]);
+        
+        assert_eq!(client.list_all_codes(&0, &2), Vec::from_array(&env, [codes[0].clone()]));
+        assert_eq!(client.list_all_codes(&2, &2), Vec::from_array(&env, [codes[2].clone(), codes[3].clone()]));
+        assert_eq!(client.list_all_codes(&4, &2), Vec::from_array(&env, [codes[4].clone()]));
+        assert_eq!(client.list_all_codes(&5, &2).len(), 0);
+    }
+    
+    #[test]
+    fn test_register_assets_batch_skips_in_batch_duplicates() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        
+        // The second USDC entry conflicts even though its supply differs, as does a
+        // repeat of an entry that failed validation
+        let entries = Vec::from_array(&env, [
+            (usdc.clone(), 1000, description.clone()),
+            (eurc.clone(), -1, description.clone()),
+            (usdc.clone(), 2000, description.clone()),
+            (eurc.clone(), 1000, description.clone()),
+        ]);
+        let results = client.register_assets_batch(&issuer, &entries);
+        assert_eq!(results, Vec::from_array(&env, [
+            RegisterResult::Registered,
+            RegisterResult::InvalidSupply,
+            RegisterResult::Conflict,
+            RegisterResult::Conflict,
+        ]));
+        assert_eq!(client.get_total_assets(), 1);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 1000);
+        assert!(client.get_asset_info(&eurc).is_none());
+    }
+    
+    #[test]
+    fn test_get_twas() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        
+        // Supply steps: 100 from t=100, 300 from t=110, 0 from t=130
+        env.ledger().set_timestamp(100);
+        client.register_asset(&usdc, &issuer, &100, &String::from_str(&env, "Test asset"));
+        env.ledger().set_timestamp(110);
+        client.update_asset_supply(&issuer, &usdc, &300);
+        env.ledger().set_timestamp(130);
+        client.update_asset_supply(&issuer, &usdc, &0);
+        
+        // (100 * 10 + 300 * 20 + 0 * 10) / 40
+        assert_eq!(client.get_twas(&usdc, &100, &140), Some(175));
+        // (100 * 5 + 300 * 10) / 15
+        assert_eq!(client.get_twas(&usdc, &105, &120), Some(233));
+        // The time before the first checkpoint counts at its supply:
+        // (100 * 110 + 300 * 20) / 130, rounded down
+        assert_eq!(client.get_twas(&usdc, &0, &130), Some(130));
+        assert_eq!(client.get_twas(&usdc, &115, &115), Some(300));
+        
+        assert_eq!(client.get_twas(&usdc, &140, &100), None);
+        assert_eq!(client.get_twas(&String::from_str(&env, "NONE"), &0, &10), None);
+    }
+    
+    #[test]
+    fn test_admin_reassign_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let admin = Address::generate(&env);
+        let client = setup_with_admin(&env, &admin);
+        let issuer = Address::generate(&env);
+        let recovery = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &String::from_str(&env, "Test asset"));
+        client.set_delegate(&usdc, &Address::generate(&env));
+        
+        // Only the admin signs; the current issuer takes no part
+        env.mock_auths(&[MockAuth {
+            address: &admin,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "admin_reassign_issuer",
+                args: (usdc.clone(), recovery.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.admin_reassign_issuer(&usdc, &recovery);
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(topics, (symbol_short!("transfer"), usdc.clone()).into_val(&env));
+        let (_, payload): (u64, (Address, Address, bool)) = data.into_val(&env);
+        assert_eq!(payload, (issuer.clone(), recovery.clone(), true));
+        
+        env.mock_all_auths();
+        assert_eq!(client.get_asset_info(&usdc).unwrap().issuer, recovery);
+        assert_eq!(client.get_delegate(&usdc), None);
+        assert_eq!(client.get_issuer_summary(&issuer).asset_count, 0);
+        assert_eq!(client.get_issuer_summary(&recovery).asset_count, 1);
+        
+        // The old issuer no longer controls the asset
+        assert!(client.try_update_asset_supply(&issuer, &usdc, &2000).is_err());
+        client.update_asset_supply(&recovery, &usdc, &2000);
+    }
+    
+    #[test]
+    fn test_not_found_code_is_reserved() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let not_found = String::from_str(&env, "NOT_FOUND");
+        
+        assert_eq!(client.can_register(&not_found, &issuer, &1000), RegisterResult::InvalidCode);
+        assert!(client.try_register_asset(&not_found, &issuer, &1000, &description).is_err());
+        assert!(client.try_update_asset_supply(&issuer, &not_found, &1000).is_err());
+        assert_eq!(client.get_total_assets(), 0);
+        
+        // A missing lookup returns nothing rather than a placeholder record
+        assert!(client.get_asset_info(&String::from_str(&env, "USDC")).is_none());
+    }
+    
+    #[test]
+    fn test_get_asset_hash() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &String::from_str(&env, "Test asset"));
+        
+        let hash = client.get_asset_hash(&usdc).unwrap();
+        assert_eq!(client.get_asset_hash(&usdc), Some(hash.clone()));
+        let raw = client.get_asset_raw(&usdc).unwrap();
+        let expected: BytesN<32> = env.crypto().sha256(&raw).into();
+        assert_eq!(hash, expected);
+        
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_ne!(client.get_asset_hash(&usdc), Some(hash));
+        assert_eq!(client.get_asset_hash(&String::from_str(&env, "NONE")), None);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_min_description_len() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        let short = String::from_str(&env, "Coin");
+        let long = String::from_str(&env, "A USD stablecoin");
+        
+        client.set_min_description_len(&10);
+        assert!(client.try_register_asset(&usdc, &issuer, &1000, &short).is_err());
+        assert_eq!(client.register_asset_checked(&usdc, &issuer, &1000, &short), RegisterResult::InvalidDescription);
+        client.register_asset(&usdc, &issuer, &1000, &long);
+        
+        assert!(client.try_update_description(&usdc, &short).is_err());
+        assert_eq!(client.get_asset_info(&usdc).unwrap().description, long);
+        
+        // 0 turns the check off again
+        client.set_min_description_len(&0);
+        client.register_asset(&eurc, &issuer, &1000, &short);
+        client.update_description(&usdc, &short);
+    }
+    
+    #[test]
+    fn test_get_recent_activity() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        let eurc = String::from_str(&env, "EURC");
+        assert_eq!(client.get_recent_activity(&10).len(), 0);
+        
+        env.ledger().set_timestamp(10);
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        env.ledger().set_timestamp(20);
+        client.register_asset(&eurc, &issuer, &1000, &description);
+        env.ledger().set_timestamp(30);
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        env.ledger().set_timestamp(40);
+        client.remove_asset(&eurc);
+        
+        let recent = client.get_recent_activity(&3);
+        assert_eq!(recent, Vec::from_array(&env, [
+            Activity { kind: symbol_short!("remove"), asset_code: eurc.clone(), timestamp: 40 },
+            Activity { kind: symbol_short!("supply"), asset_code: usdc.clone(), timestamp: 30 },
+            Activity { kind: symbol_short!("register"), asset_code: eurc.clone(), timestamp: 20 },
+        ]));
+        
+        // Once full, the oldest entries are overwritten
+        for supply in 0..MAX_ACTIVITY {
+            client.update_asset_supply(&issuer, &usdc, &(supply as i128));
+        }
+        let recent = client.get_recent_activity(&100);
+        assert_eq!(recent.len(), MAX_ACTIVITY);
+        assert!(recent.iter().all(|entry| entry.kind == symbol_short!("supply")));
+    }
+    
+    #[test]
+    fn test_register_and_update_require_issuer_auth() {
+        let env = Env::default();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let attacker = Address::generate(&env);
+        let description = String::from_str(&env, "Test asset");
+        let usdc = String::from_str(&env, "USDC");
+        
+        // Nobody signs
+        assert!(client.try_register_asset(&usdc, &issuer, &1000, &description).is_err());
+        
+        env.mock_auths(&[MockAuth {
+            address: &issuer,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "register_asset",
+                args: (usdc.clone(), issuer.clone(), 1000i128, description.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.register_asset(&usdc, &issuer, &1000, &description);
+        
+        // The attacker signs for itself, but is neither the issuer nor its delegate
+        env.mock_auths(&[MockAuth {
+            address: &attacker,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "update_asset_supply",
+                args: (attacker.clone(), usdc.clone(), 5i128).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        assert_eq!(client.try_update_asset_supply(&attacker, &usdc, &5), Err(Ok(Error::NotIssuerOrDelegate)));
+        
+        // Naming the issuer as caller without its signature fails too
+        env.mock_auths(&[]);
+        assert!(client.try_update_asset_supply(&issuer, &usdc, &5).is_err());
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 1000);
+        
+        env.mock_all_auths();
+        client.update_asset_supply(&issuer, &usdc, &2000);
+        assert_eq!(client.get_asset_info(&usdc).unwrap().total_supply, 2000);
+    }
+    
+    #[test]
+    fn test_transfer_issuer() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let client = setup(&env);
+        let issuer = Address::generate(&env);
+        let new_issuer = Address::generate(&env);
+        let usdc = String::from_str(&env, "USDC");
+        client.register_asset(&usdc, &issuer, &1000, &String::from_str(&env, "Test asset"));
+        
+        // Only the current issuer may hand the asset off
+        env.mock_auths(&[]);
+        assert!(client.try_transfer_issuer(&usdc, &new_issuer).is_err());
+        
+        env.mock_auths(&[MockAuth {
+            address: &issuer,
+            invoke: &MockAuthInvoke {
+                contract: &client.address,
+                fn_name: "transfer_issuer",
+                args: (usdc.clone(), new_issuer.clone()).into_val(&env),
+                sub_invokes: &[],
+            },
+        }]);
+        client.transfer_issuer(&usdc, &new_issuer);
+        let (_, _, data) = env.events().all().last().unwrap();
+        let (_, payload): (u64, (Address, Address, bool)) = data.into_val(&env);
+        assert_eq!(payload, (issuer.clone(), new_issuer.clone(), false));
+        
+        env.mock_all_auths();
+        assert_eq!(client.get_asset_info(&usdc).unwrap().issuer, new_issuer);
+        assert!(client.try_update_asset_supply(&issuer, &usdc, &2000).is_err());
+        client.update_asset_supply(&new_issuer, &usdc, &2000);
+    }
+}